@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Credentials {
     pub key: String,
     pub secret: String,
@@ -32,4 +32,10 @@ pub struct ProfileCredentials {
     pub profile_name: String,
     pub region_name: String,
     pub credentials: Credentials,
+    /// The ARN of the assumed role, if the profile assumes one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_arn: Option<String>,
+    /// `ca_bundle` of the profile, for the commands which call AWS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
 }
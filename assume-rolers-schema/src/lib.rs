@@ -1,3 +1,4 @@
 pub mod credentials;
 pub mod plugin;
 pub mod shell;
+pub mod variables;
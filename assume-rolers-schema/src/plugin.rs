@@ -4,23 +4,38 @@ use serde::{Deserialize, Serialize};
 
 use crate::credentials::ProfileCredentials;
 use crate::shell::Shell;
+use crate::variables::{Variable, VariableMapping};
 
 #[derive(Serialize, Deserialize)]
 pub struct PluginPayload {
     pub version: String,
     pub shell: Option<Shell>,
     pub credentials: ProfileCredentials,
+    #[serde(default)]
+    pub variables: Option<Vec<Variable>>,
 }
 
 impl PluginPayload {
-    pub fn new(shell: Option<Shell>, credentials: ProfileCredentials) -> PluginPayload {
+    pub fn new(
+        shell: Option<Shell>,
+        credentials: ProfileCredentials,
+        variables: Vec<Variable>,
+    ) -> PluginPayload {
         PluginPayload {
             version: env!("CARGO_PKG_VERSION").to_string(),
             shell,
             credentials,
+            variables: Some(variables),
         }
     }
 
+    pub fn variables(&self) -> Vec<Variable> {
+        // Payloads sent by older hosts don't contain variables, so fall back to the default mapping.
+        self.variables
+            .clone()
+            .unwrap_or_else(|| VariableMapping::default().variables(&self.credentials))
+    }
+
     pub fn from_stdin() -> anyhow::Result<PluginPayload> {
         let mut json = String::new();
         io::stdin().read_to_string(&mut json)?;
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::credentials::ProfileCredentials;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl Variable {
+    pub fn set<N: Into<String>, V: Into<String>>(name: N, value: V) -> Variable {
+        Variable {
+            name: name.into(),
+            value: Some(value.into()),
+        }
+    }
+
+    pub fn unset<N: Into<String>>(name: N) -> Variable {
+        Variable {
+            name: name.into(),
+            value: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableAction {
+    /// Export the value, or unset the variable if the value is missing.
+    Set,
+    /// Always unset the variable.
+    Unset,
+    /// Leave the variable untouched.
+    Skip,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VariableRule {
+    pub action: Option<VariableAction>,
+    pub rename: Option<String>,
+}
+
+struct StandardVariable {
+    name: &'static str,
+    action: VariableAction,
+    value: fn(&ProfileCredentials) -> Option<String>,
+}
+
+const STANDARD_VARIABLES: &[StandardVariable] = &[
+    // for AWS SDK, aws-cli
+    StandardVariable {
+        name: "AWS_PROFILE",
+        action: VariableAction::Unset,
+        value: |_| None,
+    },
+    StandardVariable {
+        name: "AWS_REGION",
+        action: VariableAction::Set,
+        value: |c| Some(c.region_name.clone()).filter(|s| !s.is_empty()),
+    },
+    StandardVariable {
+        name: "AWS_DEFAULT_REGION",
+        action: VariableAction::Set,
+        value: |c| Some(c.region_name.clone()).filter(|s| !s.is_empty()),
+    },
+    StandardVariable {
+        name: "AWS_ACCESS_KEY_ID",
+        action: VariableAction::Set,
+        value: |c| Some(c.credentials.key().to_string()),
+    },
+    StandardVariable {
+        name: "AWS_SECRET_ACCESS_KEY",
+        action: VariableAction::Set,
+        value: |c| Some(c.credentials.secret().to_string()),
+    },
+    StandardVariable {
+        name: "AWS_SESSION_TOKEN",
+        action: VariableAction::Set,
+        value: |c| c.credentials.token().map(|s| s.to_string()),
+    },
+    StandardVariable {
+        name: "AWS_SESSION_EXPIRATION",
+        action: VariableAction::Set,
+        value: |c| c.credentials.expires_at().map(|dt| dt.to_rfc3339()),
+    },
+    // legacy names, disabled by default
+    StandardVariable {
+        name: "AWS_SECURITY_TOKEN",
+        action: VariableAction::Skip,
+        value: |c| c.credentials.token().map(|s| s.to_string()),
+    },
+    StandardVariable {
+        name: "AWS_CREDENTIAL_EXPIRATION",
+        action: VariableAction::Skip,
+        value: |c| c.credentials.expires_at().map(|dt| dt.to_rfc3339()),
+    },
+    // for prompts
+    StandardVariable {
+        name: "ASSUME_ROLERS_PROFILE",
+        action: VariableAction::Set,
+        value: |c| Some(c.profile_name.clone()),
+    },
+];
+
+/// Maps the standard variable names to the rules overriding their default behaviour.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VariableMapping {
+    rules: BTreeMap<String, VariableRule>,
+}
+
+impl VariableMapping {
+    pub fn standard_names() -> impl Iterator<Item = &'static str> {
+        STANDARD_VARIABLES.iter().map(|v| v.name)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let unknown = self
+            .rules
+            .keys()
+            .filter(|k| !Self::standard_names().any(|n| n == k.as_str()))
+            .map(|k| k.as_str())
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "unknown variables: {}. available variables: {}",
+                unknown.join(", "),
+                Self::standard_names().collect::<Vec<_>>().join(", ")
+            ))
+        }
+    }
+
+    pub fn variables(&self, credentials: &ProfileCredentials) -> Vec<Variable> {
+        STANDARD_VARIABLES
+            .iter()
+            .filter_map(|v| {
+                let rule = self.rules.get(v.name);
+                let name = rule
+                    .and_then(|r| r.rename.as_deref())
+                    .unwrap_or(v.name)
+                    .to_string();
+                match rule.and_then(|r| r.action).unwrap_or(v.action) {
+                    VariableAction::Set => Some(Variable {
+                        name,
+                        value: (v.value)(credentials),
+                    }),
+                    VariableAction::Unset => Some(Variable::unset(name)),
+                    VariableAction::Skip => None,
+                }
+            })
+            .collect()
+    }
+}
@@ -2,3 +2,20 @@ complete -c assume-rolers -a '(assume-rolers -l)'
 complete -c assume-rolers -s t -l token -f -r -d 'Specify a token code provided by the MFA device.'
 complete -c assume-rolers -s p -l plugin -f -r -a 'export federation (__fish_complete_suffix --or-files .wasm)'
 complete -c assume-rolers -x -s l -l list -d 'Show available profiles.'
+complete -c assume-rolers -s g -l group -x -r -d 'Show profiles in the group defined in the config, or run for each of them.'
+complete -c assume-rolers -s m -l multi -d 'Select multiple profiles with tab, and run for each of them.'
+complete -c assume-rolers -l account -x -r -d 'Filter profiles by the account ID of the role.'
+complete -c assume-rolers -l role-name -x -r -d 'Filter profiles by the name of the role.'
+complete -c assume-rolers -l match -x -r -d 'Filter profiles by a regular expression matched against the name.'
+complete -c assume-rolers -l tag -x -r -d 'Filter profiles by a tag in key=value format, defined in the config.'
+complete -c assume-rolers -l requires-mfa -d 'Filter profiles which require MFA.'
+complete -c assume-rolers -l long -d 'Show the profiles with details in a table.'
+complete -c assume-rolers -s o -l output -x -r -a 'json yaml csv' -d 'Show the profiles with details in the format.'
+complete -c assume-rolers -l all -d 'Show profiles without a role as well, such as static keys and SSO.'
+complete -c assume-rolers -l config-file -r -F -d 'Load an additional AWS config file.'
+complete -c assume-rolers -l credentials-file -r -F -d 'Load an additional AWS credentials file.'
+complete -c assume-rolers -l role -x -r -d 'Assume the role in the account given by --account, without a profile.'
+complete -c assume-rolers -l role-arn -x -r -d 'Assume the role without a profile.'
+complete -c assume-rolers -l via -x -r -a '(assume-rolers -l --all)' -d 'Specify the source profile of --role or --role-arn.'
+complete -c assume-rolers -l from-env -d 'Assume the role with the credentials in the environment variables.'
+complete -c assume-rolers -l endpoint-url -x -r -d 'Override the endpoint of STS.'
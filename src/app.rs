@@ -1,40 +1,63 @@
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::io::IsTerminal;
 use std::path::Path;
 
 use crate::assume_role::aws_sdk::AwsSdkAssumeRole;
+use crate::assume_role::cache::CachedAssumeRole;
+use crate::command::exec::ExecCommand;
+use crate::command::export::ExportCommand;
 use crate::command::federation::FederationCommand;
 use crate::command::shell::ShellCommand;
 use crate::command::wasm::WasmCommand;
-use crate::command::Command;
-use crate::mfa::{ReadMfaToken, StaticMfaTokenReader, StdinMfaTokenReader};
+use crate::command::{Command, Exports};
+use crate::config::cli::ShowConfig;
+use crate::config::Config;
+use crate::history::cli::ShowHistory;
+use crate::history::History;
+use crate::mfa::MfaReader;
+use crate::network::NetworkSettings;
+use crate::output::OutputFormat;
+use crate::profile::arn::{is_account_id, RoleArn};
+use crate::profile::cli::{profile_command, EditProfile, Graph, Lint, ListProfiles, NewProfile};
+use crate::profile::files::{ProfileFiles, CONFIG_FILE_FLAG, CREDENTIALS_FILE_FLAG};
+use crate::profile::filter::{ProfileFilter, Tag};
+use crate::profile::generate::cli::{Discover, SsoPopulate};
 use crate::profile::load::aws_sdk::AwsSdkProfileLoader;
+use crate::profile::load::config::ConfigProfileLoader;
+use crate::profile::load::dynamic::{DynamicProfileLoader, DynamicRole};
 use crate::profile::load::LoadProfiles;
+use crate::profile::resolve::{NameResolver, Resolution};
+use crate::profile::select::external::ExternalProfileSelector;
 use crate::profile::select::skim::SkimProfileSelector;
-use crate::profile::select::{SelectProfile, StaticProfileSelector};
+use crate::profile::select::tty::TtyProfileSelector;
+use crate::profile::select::{
+    ProfileOrder, SelectProfile, SelectProfiles, StaticProfileSelector, StaticProfilesSelector,
+};
 use crate::profile::{Profile, ProfileSet};
+use crate::project::cli::{Direnv, Trust};
+use crate::project::ProjectFile;
 use crate::run::AssumeRolers;
+use crate::settings::{Layer, Setting, Settings};
 use assume_rolers_schema::credentials::ProfileCredentials;
 use async_trait::async_trait;
-use clap::builder::{PossibleValue, TypedValueParser};
-use clap::ArgAction;
-
-fn builtin_commands() -> HashMap<&'static str, CredentialsCommand> {
-    fn wasm_command(name: &str, binary: Vec<u8>) -> CredentialsCommand {
-        CredentialsCommand::WasmPlugin(WasmCommand::from_binary(name, binary))
-    }
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser, ValueParser};
+use clap::{ArgAction, ArgGroup};
+use regex::Regex;
+use tracing::debug;
 
+fn builtin_commands(
+    exports: &Exports,
+    network: &NetworkSettings,
+) -> HashMap<&'static str, CredentialsCommand> {
     HashMap::from([
         (
             "export",
-            wasm_command(
-                "export",
-                include_bytes!("../assets/assume-rolers-export.wasm").to_vec(),
-            ),
+            CredentialsCommand::Export(ExportCommand::new(exports.clone())),
         ),
         (
             "federation",
-            CredentialsCommand::Federation(FederationCommand),
+            CredentialsCommand::Federation(FederationCommand::new(network.clone())),
         ),
     ])
 }
@@ -47,14 +70,37 @@ async fn profile_names<L: LoadProfiles>(loader: L) -> anyhow::Result<Vec<String>
         .collect::<Vec<_>>())
 }
 
+fn not_found_message(query: &str, suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        format!("No profile found. profile_name:{}", query)
+    } else {
+        format!(
+            "No profile found. profile_name:{}. did you mean: {}",
+            query,
+            suggestions.join(", ")
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ProfileNameParser {
     profile_names: Vec<String>,
+    aliases: Vec<(String, String)>,
+    resolver: NameResolver,
+    /// False if the user config is broken, since the aliases are unknown.
+    strict: bool,
 }
 
-impl From<Vec<String>> for ProfileNameParser {
-    fn from(profile_names: Vec<String>) -> Self {
-        ProfileNameParser { profile_names }
+impl ProfileNameParser {
+    fn new(profile_names: Vec<String>, config: &Config, strict: bool) -> Self {
+        let aliases = config.alias_pairs(&profile_names);
+        let resolver = NameResolver::new(&profile_names, &aliases);
+        ProfileNameParser {
+            profile_names,
+            aliases,
+            resolver,
+            strict,
+        }
     }
 }
 
@@ -72,25 +118,42 @@ impl TypedValueParser for ProfileNameParser {
             .into_string()
             .map_err(|_| clap::Error::new(clap::error::ErrorKind::InvalidUtf8).with_cmd(cmd))?;
 
-        if self.profile_names.contains(&value) {
-            Ok(value)
-        } else {
-            Err(clap::Error::new(clap::error::ErrorKind::InvalidValue).with_cmd(cmd))
+        // `-` means the last assumed profile.
+        if value == LAST_PROFILE {
+            return Ok(value);
+        }
+        match self.resolver.resolve(&value) {
+            Resolution::Found(name) => Ok(name),
+            // resolved later by the selector with the value as the query.
+            Resolution::Ambiguous(_) => Ok(value),
+            // the command fails on the broken config instead.
+            Resolution::NotFound(_) if !self.strict => Ok(value),
+            Resolution::NotFound(suggestions) => Err(clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                format!("{}\n", not_found_message(&value, &suggestions)),
+            )
+            .with_cmd(cmd)),
         }
     }
 
     fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let aliases = self
+            .aliases
+            .iter()
+            .map(|(alias, target)| PossibleValue::new(alias).help(format!("alias of {}", target)));
         Some(Box::new(
             self.profile_names
-                .clone()
-                .into_iter()
-                .map(PossibleValue::new),
+                .iter()
+                .map(PossibleValue::new)
+                .chain(aliases),
         ))
     }
 }
 
 enum ProfileSelector {
-    Skim(SkimProfileSelector),
+    Skim(Box<SkimProfileSelector>),
+    External(ExternalProfileSelector),
+    Tty(TtyProfileSelector),
     Static(StaticProfileSelector),
 }
 
@@ -99,48 +162,99 @@ impl SelectProfile for ProfileSelector {
         use ProfileSelector::*;
         match self {
             Skim(s) => s.select_profile(profiles),
+            External(s) => s.select_profile(profiles),
+            Tty(s) => s.select_profile(profiles),
             Static(s) => s.select_profile(profiles),
         }
     }
 }
 
-fn selector_from(assume_role: &AssumeRole) -> ProfileSelector {
-    if let Some(profile) = assume_role.profile.as_ref() {
-        ProfileSelector::Static(StaticProfileSelector::from(profile.to_string()))
-    } else {
-        ProfileSelector::Skim(SkimProfileSelector)
-    }
-}
-
-#[derive(Clone)]
-enum MfaReader {
-    Stdin(StdinMfaTokenReader),
-    Static(StaticMfaTokenReader),
+enum ProfilesSelector {
+    Skim(Box<SkimProfileSelector>),
+    External(ExternalProfileSelector),
+    Tty(TtyProfileSelector),
+    Static(StaticProfilesSelector),
 }
 
-#[async_trait]
-impl ReadMfaToken for MfaReader {
-    async fn read_mfa_token(&self, mfa_serial: &str) -> anyhow::Result<String> {
-        use MfaReader::*;
+impl SelectProfiles for ProfilesSelector {
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>> {
+        use ProfilesSelector::*;
         match self {
-            Stdin(r) => r.read_mfa_token(mfa_serial).await,
-            Static(r) => r.read_mfa_token(mfa_serial).await,
+            Skim(s) => s.select_profiles(profiles),
+            External(s) => s.select_profiles(profiles),
+            Tty(s) => s.select_profiles(profiles),
+            Static(s) => s.select_profiles(profiles),
         }
     }
 }
 
-fn mfa_reader_from(assume_role: &AssumeRole) -> MfaReader {
-    if let Some(token) = assume_role.token.as_ref() {
-        MfaReader::Static(StaticMfaTokenReader::from(token))
+const LAST_PROFILE: &str = "-";
+
+/// `query` is an ambiguous profile name, which pre-filters the skim selector.
+fn selector_from(
+    settings: &Settings,
+    config: &Config,
+    order: ProfileOrder,
+    query: Option<(String, Vec<String>)>,
+) -> anyhow::Result<ProfileSelector> {
+    if let Some(profile) = settings.profile.as_ref() {
+        Ok(ProfileSelector::Static(StaticProfileSelector::from(
+            profile.value.to_string(),
+        )))
+    } else if let Some((query, candidates)) = query
+        .as_ref()
+        .filter(|_| config.selector.finder.is_some() || !std::io::stdin().is_terminal())
+    {
+        Err(anyhow::anyhow!(
+            "ambiguous profile name. profile_name:{}, candidates: {}",
+            query,
+            candidates.join(", ")
+        ))
+    } else if let Some(finder) = config.selector.finder.clone() {
+        Ok(ProfileSelector::External(ExternalProfileSelector::new(
+            finder, order,
+        )))
+    } else if std::io::stdin().is_terminal() {
+        let selector = SkimProfileSelector::from(config)
+            .with_order(order)
+            .with_query(query.map(|(query, _)| query));
+        Ok(ProfileSelector::Skim(Box::new(selector)))
+    } else {
+        Ok(ProfileSelector::Tty(TtyProfileSelector::from(order)))
+    }
+}
+
+fn profiles_selector_from(
+    group: Option<&String>,
+    config: &Config,
+    order: ProfileOrder,
+    filter: ProfileFilter,
+) -> anyhow::Result<ProfilesSelector> {
+    if let Some(group) = group {
+        let members = config
+            .group(group)
+            .ok_or_else(|| anyhow::anyhow!("No group found. group:{}", group))?;
+        let selector = StaticProfilesSelector::from(members).with_filter(filter);
+        Ok(ProfilesSelector::Static(selector))
+    } else if let Some(finder) = config.selector.finder.clone() {
+        Ok(ProfilesSelector::External(ExternalProfileSelector::new(
+            finder, order,
+        )))
+    } else if std::io::stdin().is_terminal() {
+        let selector = SkimProfileSelector::from(config).with_order(order);
+        Ok(ProfilesSelector::Skim(Box::new(selector)))
     } else {
-        MfaReader::Stdin(StdinMfaTokenReader)
+        Ok(ProfilesSelector::Tty(TtyProfileSelector::from(order)))
     }
 }
 
+#[derive(Clone)]
 enum CredentialsCommand {
     Shell(ShellCommand),
     WasmPlugin(WasmCommand),
+    Export(ExportCommand),
     Federation(FederationCommand),
+    Exec(ExecCommand),
 }
 
 #[async_trait]
@@ -150,18 +264,31 @@ impl Command for CredentialsCommand {
         match self {
             Shell(command) => command.run(credentials).await,
             WasmPlugin(command) => command.run(credentials).await,
+            Export(command) => command.run(credentials).await,
             Federation(command) => command.run(credentials).await,
+            Exec(command) => command.run(credentials).await,
         }
     }
 }
 
-fn credentials_command_from(assume_role: &AssumeRole) -> anyhow::Result<CredentialsCommand> {
-    if let Some(plugin) = assume_role.plugin.as_ref() {
+fn credentials_command_from(
+    settings: &Settings,
+    config: &Config,
+    exec: &[String],
+) -> anyhow::Result<CredentialsCommand> {
+    let exports = config.exports();
+    if let Some((program, args)) = exec.split_first() {
+        Ok(CredentialsCommand::Exec(ExecCommand::new(
+            program.to_string(),
+            args.to_vec(),
+            exports,
+        )))
+    } else if let Some(Setting { value: plugin, .. }) = settings.plugin.as_ref() {
         let file_ext = Path::new(plugin).extension().and_then(|s| s.to_str());
-        let mut commands = builtin_commands();
+        let mut commands = builtin_commands(&exports, &NetworkSettings::from(&config.network));
         if let Some("wasm") = file_ext {
             Ok(CredentialsCommand::WasmPlugin(WasmCommand::from_file(
-                plugin,
+                plugin, exports,
             )))
         } else if let Some(command) = commands.remove(plugin.as_str()) {
             Ok(command)
@@ -171,21 +298,42 @@ fn credentials_command_from(assume_role: &AssumeRole) -> anyhow::Result<Credenti
             ))
         }
     } else {
-        Ok(CredentialsCommand::Shell(ShellCommand))
+        Ok(CredentialsCommand::Shell(ShellCommand::new(exports)))
     }
 }
 
-pub async fn app() -> anyhow::Result<clap::Command> {
-    let profile_names = profile_names(AwsSdkProfileLoader::default()).await?;
-    let name_parser = ProfileNameParser::from(profile_names);
+/// `config` is None if the user config is broken, which fails the commands needing it after parsing.
+pub async fn app(config: Option<&Config>, files: &ProfileFiles) -> anyhow::Result<clap::Command> {
+    let strict = config.is_some();
+    let default = Config::default();
+    let config = config.unwrap_or(&default);
+    // broken AWS config shouldn't prevent `lint` and `--help`. other commands fail on loading it again.
+    let profiles = ConfigProfileLoader::new(AwsSdkProfileLoader::from(files), config.clone())
+        .load_profiles()
+        .await
+        .map_err(|e| debug!("cannot load profiles. error:{:?}", e))
+        .ok();
+    let names = |f: fn(&Profile) -> bool| {
+        profiles
+            .iter()
+            .flat_map(|p| p.profiles())
+            .filter(|&p| f(p))
+            .map(|p| p.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    let name_parser = ProfileNameParser::new(names(Profile::has_role_arn), config, strict);
+    // any profile can be the source of a dynamic role.
+    let source_name_parser = ProfileNameParser::new(names(|_| true), config, strict);
+    let group_names = config.groups.keys().cloned().collect::<Vec<_>>();
 
     Ok(clap::Command::new("assume-rolers")
+        .version(env!("CARGO_PKG_VERSION"))
         .disable_colored_help(false)
         .arg(
             clap::Arg::new("profile")
                 .value_hint(clap::ValueHint::Other)
                 .value_parser(name_parser)
-                .help("Specify a profile to assume."),
+                .help("Specify a profile to assume. `-` means the last assumed profile."),
         )
         .arg(
             clap::Arg::new("token")
@@ -206,9 +354,185 @@ pub async fn app() -> anyhow::Result<clap::Command> {
                 .short('l')
                 .long("list")
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["profile", "token"])
+                .conflicts_with_all(["profile", "token", "multi", "command"])
                 .help("Show available profiles."),
-        ))
+        )
+        .arg(
+            clap::Arg::new("long")
+                .long("long")
+                .requires("list")
+                .action(ArgAction::SetTrue)
+                .help("Show the profiles with details in a table."),
+        )
+        .arg(
+            clap::Arg::new("output")
+                .short('o')
+                .long("output")
+                .requires("list")
+                .conflicts_with("long")
+                .value_parser(
+                    PossibleValuesParser::new(OutputFormat::NAMES)
+                        .try_map(|s| s.parse::<OutputFormat>()),
+                )
+                .help("Show the profiles with details in the format."),
+        )
+        .arg(
+            clap::Arg::new("all")
+                .long("all")
+                .requires("list")
+                .action(ArgAction::SetTrue)
+                .help("Show profiles without a role as well, such as static keys and SSO."),
+        )
+        .arg(
+            clap::Arg::new("group")
+                .short('g')
+                .long("group")
+                .conflicts_with_all(["profile", "multi"])
+                .value_parser(PossibleValuesParser::new(group_names))
+                .help("Show profiles in the group defined in the config, or run for each of them."),
+        )
+        .arg(
+            clap::Arg::new("multi")
+                .short('m')
+                .long("multi")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("profile")
+                .help("Select multiple profiles with tab, and run for each of them."),
+        )
+        .arg(
+            clap::Arg::new("region")
+                .long("region")
+                .value_hint(clap::ValueHint::Other)
+                .help("Specify a region to override the profile's region."),
+        )
+        .arg(
+            clap::Arg::new("account")
+                .long("account")
+                .action(ArgAction::Append)
+                .conflicts_with("profile")
+                .value_hint(clap::ValueHint::Other)
+                .help("Filter profiles by the account ID of the role, or specify the account of --role."),
+        )
+        .arg(
+            clap::Arg::new("role-name")
+                .long("role-name")
+                .action(ArgAction::Append)
+                .conflicts_with("profile")
+                .value_hint(clap::ValueHint::Other)
+                .help("Filter profiles by the name of the role."),
+        )
+        .arg(
+            clap::Arg::new("match")
+                .long("match")
+                .conflicts_with("profile")
+                .value_parser(|s: &str| Regex::new(s))
+                .value_hint(clap::ValueHint::Other)
+                .help("Filter profiles by a regular expression matched against the name."),
+        )
+        .arg(
+            clap::Arg::new("tag")
+                .long("tag")
+                .action(ArgAction::Append)
+                .conflicts_with("profile")
+                .value_parser(|s: &str| s.parse::<Tag>())
+                .value_hint(clap::ValueHint::Other)
+                .help("Filter profiles by a tag in key=value format, defined in the config."),
+        )
+        .arg(
+            clap::Arg::new("requires-mfa")
+                .long("requires-mfa")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("profile")
+                .help("Filter profiles which require MFA."),
+        )
+        .arg(
+            clap::Arg::new("role")
+                .long("role")
+                .requires("account")
+                .value_hint(clap::ValueHint::Other)
+                .help("Assume the role in the account given by --account, without a profile."),
+        )
+        .arg(
+            clap::Arg::new("role-arn")
+                .long("role-arn")
+                .conflicts_with_all(["account", "role"])
+                .value_parser(|s: &str| s.parse::<RoleArn>())
+                .value_hint(clap::ValueHint::Other)
+                .help("Assume the role without a profile."),
+        )
+        .group(
+            ArgGroup::new("dynamic")
+                .args(["role", "role-arn"])
+                .conflicts_with_all([
+                    "profile",
+                    "list",
+                    "group",
+                    "multi",
+                    "role-name",
+                    "match",
+                    "tag",
+                    "requires-mfa",
+                ]),
+        )
+        .arg(
+            clap::Arg::new("via")
+                .long("via")
+                .requires("dynamic")
+                .value_hint(clap::ValueHint::Other)
+                .value_parser(source_name_parser.clone())
+                .help("Specify the source profile of --role or --role-arn. Its session name, duration and MFA are used as well."),
+        )
+        .arg(
+            clap::Arg::new("from-env")
+                .long("from-env")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["list", "via"])
+                .help("Assume the role with the credentials in the environment variables, e.g. in an assumed shell. The duration is limited to an hour."),
+        )
+        .arg(
+            clap::Arg::new("endpoint-url")
+                .long("endpoint-url")
+                .conflicts_with("list")
+                .value_parser(|s: &str| s.parse::<reqwest::Url>())
+                .value_hint(clap::ValueHint::Url)
+                .help("Override the endpoint of STS, e.g. with a VPC endpoint or a local stand-in. Takes precedence over AWS_ENDPOINT_URL_STS and endpoint_url in the profile."),
+        )
+        .arg(
+            clap::Arg::new("command")
+                .num_args(1..)
+                .last(true)
+                .conflicts_with("plugin")
+                .value_hint(clap::ValueHint::CommandWithArguments)
+                .help("Run the command with the credentials instead of a shell or plugin."),
+        )
+        .arg(
+            clap::Arg::new("config-file")
+                .long(CONFIG_FILE_FLAG.trim_start_matches('-'))
+                .global(true)
+                .action(ArgAction::Append)
+                .value_hint(clap::ValueHint::FilePath)
+                .help("Load an additional AWS config file. The later ones take precedence."),
+        )
+        .arg(
+            clap::Arg::new("credentials-file")
+                .long(CREDENTIALS_FILE_FLAG.trim_start_matches('-'))
+                .global(true)
+                .action(ArgAction::Append)
+                .value_hint(clap::ValueHint::FilePath)
+                .help("Load an additional AWS credentials file. The later ones take precedence."),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(Direnv::command())
+        .subcommand(Trust::command())
+        .subcommand(Lint::command())
+        .subcommand(Discover::command(ValueParser::new(
+            source_name_parser.clone(),
+        )))
+        .subcommand(SsoPopulate::command(ValueParser::new(source_name_parser)))
+        .subcommand(profile_command())
+        .subcommand(Graph::command())
+        .subcommand(ShowHistory::command())
+        .subcommand(ShowConfig::command()))
 }
 
 #[derive(Debug)]
@@ -216,64 +540,346 @@ pub struct AssumeRole {
     profile: Option<String>,
     token: Option<String>,
     plugin: Option<String>,
+    region: Option<String>,
+    group: Option<String>,
+    multi: bool,
+    command: Vec<String>,
+    filter: ProfileFilter,
+    dynamic: Option<DynamicRole>,
+    from_env: bool,
+    endpoint_url: Option<reqwest::Url>,
 }
 
-#[derive(Debug)]
-pub struct ListProfiles;
+impl AssumeRole {
+    fn is_batch(&self) -> bool {
+        self.multi || self.group.is_some()
+    }
+
+    /// Sessions chained from the environment or from another endpoint aren't cached,
+    /// since the cache doesn't know their source.
+    fn assume_role_from(
+        &self,
+        files: &ProfileFiles,
+        config: &Config,
+    ) -> CachedAssumeRole<AwsSdkAssumeRole> {
+        let mut policy = config.cache.clone();
+        policy.enabled &= !self.from_env && self.endpoint_url.is_none();
+        CachedAssumeRole::new(
+            AwsSdkAssumeRole::from(files)
+                .with_network(NetworkSettings::from(&config.network))
+                .with_from_env(self.from_env)
+                .with_endpoint_url(self.endpoint_url.as_ref().map(|u| u.to_string())),
+            policy,
+        )
+    }
+
+    fn cli_layer(&self) -> Layer {
+        Layer {
+            profile: self.profile.clone(),
+            plugin: self.plugin.clone(),
+            region: self.region.clone(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum App {
-    AssumeRole(AssumeRole),
+    AssumeRole(Box<AssumeRole>),
     ListProfiles(ListProfiles),
+    Direnv(Direnv),
+    Trust(Trust),
+    ShowConfig(ShowConfig),
+    ShowHistory(ShowHistory),
+    Lint(Lint),
+    Graph(Graph),
+    Discover(Discover),
+    SsoPopulate(SsoPopulate),
+    EditProfile(EditProfile),
+    NewProfile(NewProfile),
+}
+
+/// Builds the role from `--role-arn`, or `--account` and `--role`. Exits on invalid arguments.
+fn dynamic_role_from(c: &mut clap::Command, matches: &clap::ArgMatches) -> Option<DynamicRole> {
+    let via = matches.get_one::<String>("via").map(|s| s.to_string());
+    if let Some(role_arn) = matches.get_one::<RoleArn>("role-arn") {
+        return Some(DynamicRole::new(role_arn.clone(), via));
+    }
+
+    let role = matches.get_one::<String>("role")?;
+    let accounts = matches
+        .get_many::<String>("account")
+        .map(|v| v.collect::<Vec<_>>())
+        .unwrap_or_default();
+    let [account] = accounts[..] else {
+        c.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "--role takes exactly one --account.",
+        )
+        .exit()
+    };
+    if !is_account_id(account) {
+        c.error(
+            clap::error::ErrorKind::InvalidValue,
+            format!("invalid account ID. account:{}", account),
+        )
+        .exit()
+    }
+    let role = DynamicRole::in_account(account, role.trim_start_matches('/'), via)
+        .unwrap_or_else(|e| c.error(clap::error::ErrorKind::InvalidValue, e).exit());
+    Some(role)
 }
 
 impl From<clap::Command> for App {
-    fn from(c: clap::Command) -> Self {
-        let matches = c.get_matches();
-        if matches.get_flag("list") {
-            App::ListProfiles(ListProfiles)
-        } else {
-            let profile = matches.get_one::<String>("profile").map(|s| s.to_string());
-            let token = matches.get_one::<String>("token").map(|s| s.to_string());
-            let plugin = matches.get_one::<String>("plugin").map(|s| s.to_string());
-            App::AssumeRole(AssumeRole {
-                profile,
-                token,
-                plugin,
-            })
+    fn from(mut c: clap::Command) -> Self {
+        let matches = c.get_matches_mut();
+        match matches.subcommand() {
+            Some(("direnv", m)) => App::Direnv(Direnv::from(m)),
+            Some(("trust", m)) => App::Trust(Trust::from(m)),
+            Some(("graph", m)) => App::Graph(Graph::from(m)),
+            Some(("discover", m)) => App::Discover(Discover::from(m)),
+            Some(("sso", m)) => App::SsoPopulate(SsoPopulate::from(m)),
+            Some(("profile", m)) => match m.subcommand().expect("subcommand is required") {
+                ("new", m) => App::NewProfile(NewProfile::from(m)),
+                (name, m) => App::EditProfile(EditProfile::from(name, m)),
+            },
+            Some(("lint", m)) => App::Lint(Lint::from(m)),
+            Some(("history", m)) => App::ShowHistory(ShowHistory::from(m)),
+            Some(("config", m)) => App::ShowConfig(ShowConfig::from(m)),
+            _ if matches.get_flag("list") => App::ListProfiles(ListProfiles::from(&matches)),
+            _ => {
+                let profile = matches.get_one::<String>("profile").map(|s| s.to_string());
+                let token = matches.get_one::<String>("token").map(|s| s.to_string());
+                let plugin = matches.get_one::<String>("plugin").map(|s| s.to_string());
+                let region = matches.get_one::<String>("region").map(|s| s.to_string());
+                let group = matches.get_one::<String>("group").map(|s| s.to_string());
+                let command = matches
+                    .get_many::<String>("command")
+                    .map(|v| v.map(|s| s.to_string()).collect())
+                    .unwrap_or_default();
+                let dynamic = dynamic_role_from(&mut c, &matches);
+                // `--account` specifies the account of `--role` rather than a filter.
+                let filter = if dynamic.is_some() {
+                    ProfileFilter::default()
+                } else {
+                    ProfileFilter::from(&matches)
+                };
+                App::AssumeRole(Box::new(AssumeRole {
+                    profile,
+                    token,
+                    plugin,
+                    region,
+                    group,
+                    multi: matches.get_flag("multi"),
+                    command,
+                    filter,
+                    dynamic,
+                    from_env: matches.get_flag("from-env"),
+                    endpoint_url: matches.get_one::<reqwest::Url>("endpoint-url").cloned(),
+                }))
+            }
         }
     }
 }
 
 impl App {
-    pub async fn run(self) -> anyhow::Result<()> {
+    /// `config` is the result of loading the user config, which fails only the commands needing it.
+    pub async fn run(
+        self,
+        config: anyhow::Result<Config>,
+        files: ProfileFiles,
+    ) -> anyhow::Result<()> {
         match self {
-            App::AssumeRole(assume_role) => Self::assume_role(assume_role).await,
-            App::ListProfiles(list_profiles) => Self::list_profiles(list_profiles).await,
+            App::AssumeRole(assume_role) => Self::assume_role(*assume_role, config?, files).await,
+            App::ListProfiles(list_profiles) => list_profiles.run(config?, files).await,
+            App::Direnv(direnv) => direnv.run(config?, files).await,
+            App::Trust(trust) => trust.run(),
+            App::ShowConfig(show_config) => show_config.run(config?, files).await,
+            App::ShowHistory(show_history) => show_history.run(),
+            App::Lint(lint) => lint.run(files),
+            App::Discover(discover) => discover.run(config?, files).await,
+            App::SsoPopulate(sso_populate) => sso_populate.run(config?, files).await,
+            App::EditProfile(edit_profile) => edit_profile.run(),
+            App::NewProfile(new_profile) => new_profile.run(config?, files).await,
+            App::Graph(graph) => graph.run(config?, files).await,
         }
     }
 
-    async fn assume_role(assume_role: AssumeRole) -> anyhow::Result<()> {
-        let selector = selector_from(&assume_role);
-        let mfa_reader = mfa_reader_from(&assume_role);
-        let command = credentials_command_from(&assume_role)?;
+    async fn assume_role(
+        assume_role: AssumeRole,
+        config: Config,
+        files: ProfileFiles,
+    ) -> anyhow::Result<()> {
+        let project = ProjectFile::discover_trusted()?;
+        let mut settings = Settings::resolve(
+            assume_role.cli_layer(),
+            project.as_ref().map(|p| &p.config),
+            &config,
+        );
+
+        // filters are for the selection, so they take precedence over the profile in the other layers.
+        if !assume_role.filter.is_empty() {
+            settings.profile = None;
+        }
+
+        if assume_role.from_env
+            && ["AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"]
+                .iter()
+                .any(|name| std::env::var_os(name).is_none())
+        {
+            anyhow::bail!(
+                "--from-env requires AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY in the environment."
+            );
+        }
+
+        let history = History::load();
+        if let Some(role) = assume_role.dynamic.clone() {
+            return Self::assume_dynamic_role(assume_role, role, settings, config, files, history)
+                .await;
+        }
+        if assume_role.is_batch() {
+            return Self::assume_roles(assume_role, settings, config, files, history).await;
+        }
+        if let Some(profile) = settings
+            .profile
+            .as_mut()
+            .filter(|p| p.value == LAST_PROFILE)
+        {
+            let last = history
+                .last()
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("no profile has been assumed yet."))?;
+            profile.value = last.profile_name.to_string();
+
+            // a dynamic role isn't in the profiles, so it's built again from the recorded role.
+            if let Some(role) = last
+                .role_arn
+                .as_deref()
+                .and_then(|arn| arn.parse::<RoleArn>().ok())
+                .map(|arn| DynamicRole::new(arn, last.source_profile.clone()))
+                .filter(|role| role.profile_name() == last.profile_name)
+            {
+                let names = profile_names(ConfigProfileLoader::new(
+                    AwsSdkProfileLoader::from(&files),
+                    config.clone(),
+                ))
+                .await?;
+                if !names.contains(&last.profile_name) {
+                    return Self::assume_dynamic_role(
+                        assume_role,
+                        role,
+                        settings,
+                        config,
+                        files,
+                        history,
+                    )
+                    .await;
+                }
+            }
+        }
+
+        // the profile may be a partial name from the command line, or the other layers.
+        let mut query = None;
+        if let Some(profile) = settings.profile.take() {
+            let names = profile_names(ConfigProfileLoader::new(
+                AwsSdkProfileLoader::from(&files),
+                config.clone(),
+            ))
+            .await?;
+            let resolver = NameResolver::new(&names, &config.alias_pairs(&names));
+            match resolver.resolve(&profile.value) {
+                Resolution::Found(name) => {
+                    settings.profile = Some(Setting {
+                        value: name,
+                        source: profile.source,
+                    })
+                }
+                Resolution::Ambiguous(candidates) => query = Some((profile.value, candidates)),
+                Resolution::NotFound(suggestions) => {
+                    anyhow::bail!(not_found_message(&profile.value, &suggestions))
+                }
+            }
+        }
+
+        let order = ProfileOrder::from(&config)
+            .with_frecency(history.frecency())
+            .with_filter(assume_role.filter.clone());
+        let selector = selector_from(&settings, &config, order, query)?;
+        let mfa_reader = MfaReader::new(assume_role.token.as_ref(), &config.mfa);
+        let command = credentials_command_from(&settings, &config, &assume_role.command)?;
         let assume_rolers = AssumeRolers::new(
-            AwsSdkProfileLoader::default(),
+            ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone()),
             selector,
             mfa_reader,
-            AwsSdkAssumeRole,
+            assume_role.assume_role_from(&files, &config),
             command,
-        );
+        )
+        .with_region(settings.region.map(|s| s.value))
+        .with_default_region(settings.default_region.map(|s| s.value))
+        .with_history(history);
         assume_rolers.run().await?;
         Ok(())
     }
 
-    async fn list_profiles(_list_profiles: ListProfiles) -> anyhow::Result<()> {
-        let profile_names = profile_names(AwsSdkProfileLoader::default()).await?;
-        for p in profile_names {
-            println!("{}", p);
+    async fn assume_dynamic_role(
+        assume_role: AssumeRole,
+        role: DynamicRole,
+        settings: Settings,
+        config: Config,
+        files: ProfileFiles,
+        history: History,
+    ) -> anyhow::Result<()> {
+        let selector = StaticProfileSelector::from(role.profile_name());
+        let mfa_reader = MfaReader::new(assume_role.token.as_ref(), &config.mfa);
+        let command = credentials_command_from(&settings, &config, &assume_role.command)?;
+        let loader = DynamicProfileLoader::new(
+            ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone()),
+            role,
+        );
+        let assume_rolers = AssumeRolers::new(
+            loader,
+            selector,
+            mfa_reader,
+            assume_role.assume_role_from(&files, &config),
+            command,
+        )
+        .with_region(settings.region.map(|s| s.value))
+        .with_default_region(settings.default_region.map(|s| s.value))
+        .with_history(history);
+        assume_rolers.run().await
+    }
+
+    async fn assume_roles(
+        assume_role: AssumeRole,
+        settings: Settings,
+        config: Config,
+        files: ProfileFiles,
+        history: History,
+    ) -> anyhow::Result<()> {
+        let order = ProfileOrder::from(&config)
+            .with_frecency(history.frecency())
+            .with_filter(assume_role.filter.clone());
+        let selector = profiles_selector_from(
+            assume_role.group.as_ref(),
+            &config,
+            order,
+            assume_role.filter.clone(),
+        )?;
+        let mfa_reader = MfaReader::new(assume_role.token.as_ref(), &config.mfa);
+        let command = credentials_command_from(&settings, &config, &assume_role.command)?;
+        if let CredentialsCommand::Shell(_) = command {
+            anyhow::bail!("batch mode requires a plugin or a command to execute.");
         }
 
-        Ok(())
+        let assume_rolers = AssumeRolers::new(
+            ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone()),
+            selector,
+            mfa_reader,
+            assume_role.assume_role_from(&files, &config),
+            command,
+        )
+        .with_region(settings.region.map(|s| s.value))
+        .with_default_region(settings.default_region.map(|s| s.value))
+        .with_history(history);
+        assume_rolers.run_batch().await
     }
 }
@@ -5,9 +5,13 @@ use crate::profile::Profile;
 use assume_rolers_schema::credentials::Credentials;
 
 pub mod aws_sdk;
+pub mod cache;
+pub mod endpoint;
 
 pub mod defaults {
     pub const DURATION_SECONDS: i32 = 3600;
+    /// STS limits the sessions of chained roles to an hour.
+    pub const MAX_CHAINED_DURATION_SECONDS: i32 = 3600;
 }
 
 pub struct AssumeRoleResult {
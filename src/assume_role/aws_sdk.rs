@@ -1,16 +1,86 @@
+use crate::assume_role::endpoint::StsEndpoint;
 use crate::assume_role::{defaults, AssumeRole, AssumeRoleResult};
 use crate::mfa::ReadMfaToken;
+use crate::network::NetworkSettings;
+use crate::profile::files::ProfileFiles;
 use crate::profile::Profile;
 use anyhow::bail;
 use assume_rolers_schema::credentials::Credentials;
 use async_trait::async_trait;
-use aws_config::BehaviorVersion;
+use aws_config::environment::EnvironmentVariableCredentialsProvider;
+use aws_config::{BehaviorVersion, ConfigLoader};
+use aws_runtime::env_config::file::EnvConfigFiles;
 use aws_sdk_sts::config::ProvideCredentials;
 use aws_sdk_sts::types::{PolicyDescriptorType, Tag};
 use aws_types::region::Region;
 use chrono::{Duration, Utc};
 
-pub struct AwsSdkAssumeRole;
+#[derive(Debug, Default)]
+pub struct AwsSdkAssumeRole {
+    profile_files: EnvConfigFiles,
+    from_env: bool,
+    endpoint_url: Option<String>,
+    network: NetworkSettings,
+}
+
+impl From<&ProfileFiles> for AwsSdkAssumeRole {
+    fn from(files: &ProfileFiles) -> Self {
+        AwsSdkAssumeRole {
+            profile_files: files.env_config_files(),
+            from_env: false,
+            endpoint_url: None,
+            network: NetworkSettings::default(),
+        }
+    }
+}
+
+impl AwsSdkAssumeRole {
+    /// Uses the credentials in the environment variables as the source, instead of the source profile.
+    pub fn with_from_env(mut self, from_env: bool) -> Self {
+        self.from_env = from_env;
+        self
+    }
+
+    /// Overrides the endpoint of STS, e.g. with a VPC endpoint or a local stand-in.
+    pub fn with_endpoint_url(mut self, endpoint_url: Option<String>) -> Self {
+        self.endpoint_url = endpoint_url;
+        self
+    }
+
+    /// The proxy, CA bundle and timeouts of the clients.
+    pub fn with_network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Loads the SDK config with the network settings, and `ca_bundle` of the profile.
+    fn sdk_defaults(&self, profile: &Profile) -> anyhow::Result<ConfigLoader> {
+        let network = self
+            .network
+            .clone()
+            .with_profile_ca_bundle(profile.endpoint.ca_bundle.as_deref());
+        Ok(aws_config::defaults(BehaviorVersion::v2024_03_28())
+            .profile_files(self.profile_files.clone())
+            .http_client(network.sdk_client()?)
+            .timeout_config(network.timeout_config()))
+    }
+
+    /// The region of the profile, or the default region of the role's partition.
+    fn sts_region(profile: &Profile) -> &str {
+        profile
+            .region_name()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| profile.partition().default_region())
+    }
+
+    fn sts_endpoint(&self, profile: &Profile) -> anyhow::Result<StsEndpoint> {
+        StsEndpoint::resolve(
+            profile,
+            self.endpoint_url.as_deref(),
+            Self::sts_region(profile),
+        )
+    }
+}
 
 struct AssumeRoleInput {
     role_arn: String,
@@ -25,7 +95,11 @@ struct AssumeRoleInput {
 }
 
 impl AssumeRoleInput {
-    async fn send(self, client: aws_sdk_sts::Client) -> anyhow::Result<AssumeRoleResult> {
+    async fn send(
+        self,
+        client: aws_sdk_sts::Client,
+        network: &NetworkSettings,
+    ) -> anyhow::Result<AssumeRoleResult> {
         let mut builder = client
             .assume_role()
             .role_arn(self.role_arn)
@@ -72,7 +146,10 @@ impl AssumeRoleInput {
             });
 
         let expires_at = Utc::now() + Duration::seconds(i64::from(self.duration_seconds));
-        let output = builder.send().await?;
+        let output = builder
+            .send()
+            .await
+            .map_err(|e| network.sdk_error("STS", e))?;
         let creds = output
             .credentials
             .ok_or_else(|| anyhow::anyhow!("assume-role didn't return a credential"))?;
@@ -100,23 +177,56 @@ impl AwsSdkAssumeRole {
         &self,
         profile: &Profile,
         input: AssumeRoleInput,
+        endpoint: StsEndpoint,
     ) -> anyhow::Result<AssumeRoleResult> {
-        let region = Region::new(profile.region_name().unwrap_or("us-east1").to_string());
+        let region = Region::new(Self::sts_region(profile).to_string());
 
-        let mut loader = aws_config::defaults(BehaviorVersion::v2024_03_28()).region(region);
-        if let Some(source_profile_name) = profile.source_profile_name() {
+        let mut loader = self
+            .sdk_defaults(profile)?
+            .region(region)
+            .use_fips(endpoint.use_fips)
+            .use_dual_stack(endpoint.use_dual_stack);
+        if self.from_env {
+            loader = loader.credentials_provider(EnvironmentVariableCredentialsProvider::new());
+        } else if let Some(source_profile_name) = profile.source_profile_name() {
             loader = loader.profile_name(source_profile_name);
         }
 
         let config = loader.load().await;
-        let client = aws_sdk_sts::Client::new(&config);
-        let result = input.send(client).await?;
+        let mut builder = aws_sdk_sts::config::Builder::from(&config);
+        if let Some(endpoint_url) = endpoint.endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        let client = aws_sdk_sts::Client::from_conf(builder.build());
+        let result = input.send(client, &self.network).await?;
         Ok(result)
     }
 
-    async fn credentials_provider(&self, profile: &Profile) -> anyhow::Result<AssumeRoleResult> {
-        let config = aws_config::defaults(BehaviorVersion::v2024_03_28())
+    /// Returns the credentials of the profile, by assuming its role or from the static keys, SSO and so on.
+    pub async fn profile_credentials<R: ReadMfaToken + Send + Sync + 'static>(
+        &self,
+        profile: &Profile,
+        mfa_reader: R,
+    ) -> anyhow::Result<AssumeRoleResult> {
+        if profile.has_role_arn() {
+            self.assume_role(profile, mfa_reader).await
+        } else {
+            let endpoint = self.sts_endpoint(profile)?;
+            self.credentials_provider(profile, endpoint).await
+        }
+    }
+
+    /// The SDK assumes the roles in the source chain, in the FIPS or dual-stack endpoints if enabled.
+    async fn credentials_provider(
+        &self,
+        profile: &Profile,
+        endpoint: StsEndpoint,
+    ) -> anyhow::Result<AssumeRoleResult> {
+        let config = self
+            .sdk_defaults(profile)?
             .profile_name(profile.name())
+            .use_fips(endpoint.use_fips)
+            .use_dual_stack(endpoint.use_dual_stack)
             .load()
             .await;
 
@@ -158,7 +268,9 @@ impl AssumeRole for AwsSdkAssumeRole {
     ) -> anyhow::Result<AssumeRoleResult> {
         // Since AWS SDK for Rust does not support MFA token code,
         // we need to assume-role manually if the profile has `mfa_serial`.
-        // Otherwise, we can use SharedCredentialsProvider.
+        // So do synthesized profiles, since they are not in the AWS config, chaining from the
+        // credentials in the environment, and custom endpoints, which the SDK doesn't apply to its
+        // STS client. Otherwise, we can use SharedCredentialsProvider.
 
         if profile.role_arn().is_none() {
             bail!(
@@ -167,7 +279,29 @@ impl AssumeRole for AwsSdkAssumeRole {
             );
         }
 
-        let result = if let Some(mfa_serial) = profile.mfa_serial() {
+        let endpoint = self.sts_endpoint(profile)?;
+        let result = if profile.mfa_serial().is_some()
+            || profile.synthesized
+            || self.from_env
+            || endpoint.endpoint_url.is_some()
+        {
+            let mut duration_seconds = profile
+                .duration_seconds()
+                .map(i32::try_from)
+                .unwrap_or(Ok(defaults::DURATION_SECONDS))?;
+            if self.from_env && duration_seconds > defaults::MAX_CHAINED_DURATION_SECONDS {
+                eprintln!(
+                    "warning: the duration is clamped to {}s, since STS limits role chaining to an hour. profile:{}, duration_seconds:{}",
+                    defaults::MAX_CHAINED_DURATION_SECONDS,
+                    profile.name(),
+                    duration_seconds
+                );
+                duration_seconds = defaults::MAX_CHAINED_DURATION_SECONDS;
+            }
+            let token_code = match profile.mfa_serial() {
+                Some(mfa_serial) => Some(mfa_reader.read_mfa_token(mfa_serial).await?),
+                None => None,
+            };
             let input = AssumeRoleInput {
                 role_arn: profile.role_arn().unwrap().to_string(),
                 role_session_name: profile
@@ -176,18 +310,15 @@ impl AssumeRole for AwsSdkAssumeRole {
                     .to_string(),
                 policy_arns: Vec::default(), // TODO
                 policy: None,                // TODO
-                duration_seconds: profile
-                    .duration_seconds()
-                    .map(i32::try_from)
-                    .unwrap_or(Ok(defaults::DURATION_SECONDS))?,
+                duration_seconds,
                 tags: Vec::default(), // TODO
                 external_id: profile.external_id().map(|s| s.to_string()),
                 mfa_serial: profile.mfa_serial().map(|s| s.to_string()),
-                token_code: Some(mfa_reader.read_mfa_token(mfa_serial).await?),
+                token_code,
             };
-            self.sts_assume_role(profile, input).await?
+            self.sts_assume_role(profile, input, endpoint).await?
         } else {
-            self.credentials_provider(profile).await?
+            self.credentials_provider(profile, endpoint).await?
         };
 
         Ok(result)
@@ -0,0 +1,168 @@
+use std::fs::{self, DirBuilder};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use assume_rolers_schema::credentials::Credentials;
+
+use crate::assume_role::{AssumeRole, AssumeRoleResult};
+use crate::atomic;
+use crate::config::CacheConfig;
+use crate::dirs;
+use crate::mfa::ReadMfaToken;
+use crate::profile::Profile;
+
+/// What the session was assumed for. A session is reused only while the profile still matches.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub profile_name: String,
+    pub role_arn: Option<String>,
+    pub source_profile: Option<String>,
+    pub partition: String,
+}
+
+impl From<&Profile> for CacheKey {
+    fn from(profile: &Profile) -> Self {
+        CacheKey {
+            profile_name: profile.name().to_string(),
+            role_arn: profile.role_arn().map(|s| s.to_string()),
+            source_profile: profile.source_profile_name().map(|s| s.to_string()),
+            partition: profile.partition().id().to_string(),
+        }
+    }
+}
+
+impl CacheKey {
+    fn file_name(&self) -> String {
+        let key = serde_json::to_string(self).expect("the key is always serializable");
+        format!("{}.json", hex::encode(Sha256::digest(key.as_bytes())))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CachedSession {
+    /// Sessions cached by older versions have no key, and are never reused.
+    #[serde(default)]
+    pub key: Option<CacheKey>,
+    pub region_name: String,
+    pub credentials: Credentials,
+}
+
+impl CachedSession {
+    pub fn remaining(&self) -> Option<Duration> {
+        self.credentials
+            .expires_at()
+            .map(|expires_at| expires_at - Utc::now())
+            .filter(|d| *d > Duration::zero())
+    }
+}
+
+/// Stores the assumed sessions under `$XDG_CACHE_HOME/assume-rolers/sessions`.
+pub struct SessionCache {
+    dir: Option<PathBuf>,
+}
+
+impl Default for SessionCache {
+    fn default() -> Self {
+        SessionCache {
+            dir: dirs::cache_dir().map(|dir| dir.join("sessions")),
+        }
+    }
+}
+
+impl SessionCache {
+    fn path(&self, key: &CacheKey) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(key.file_name()))
+    }
+
+    pub fn get(&self, profile: &Profile) -> Option<CachedSession> {
+        let key = CacheKey::from(profile);
+        let path = self.path(&key)?;
+        let json = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<CachedSession>(&json) {
+            Ok(session) if session.key.as_ref() != Some(&key) => {
+                debug!(
+                    "ignored the cache of another profile. path:{}",
+                    path.display()
+                );
+                None
+            }
+            Ok(session) => session.remaining().map(|_| session),
+            Err(e) => {
+                debug!("ignored broken cache. path:{}, error:{}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Stores the session under its key. Sessions without a key are not stored.
+    /// The session contains secrets, so only the owner can list or read them, even in an existing directory.
+    pub fn put(&self, session: &CachedSession) -> anyhow::Result<()> {
+        if let Some(path) = session.key.as_ref().and_then(|key| self.path(key)) {
+            if let Some(dir) = path.parent() {
+                DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+                fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+            }
+            atomic::write(&path, serde_json::to_string(session)?.as_bytes(), 0o600)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CachedAssumeRole<A> {
+    assume_role: A,
+    policy: CacheConfig,
+    cache: SessionCache,
+}
+
+impl<A> CachedAssumeRole<A> {
+    pub fn new(assume_role: A, policy: CacheConfig) -> Self {
+        CachedAssumeRole {
+            assume_role,
+            policy,
+            cache: SessionCache::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: AssumeRole + Send + Sync> AssumeRole for CachedAssumeRole<A> {
+    async fn assume_role<R: ReadMfaToken + Send + Sync + 'static>(
+        &self,
+        profile: &Profile,
+        mfa_reader: R,
+    ) -> anyhow::Result<AssumeRoleResult> {
+        if !self.policy.enabled {
+            return self.assume_role.assume_role(profile, mfa_reader).await;
+        }
+
+        let min_remaining = Duration::seconds(i64::from(self.policy.min_remaining_seconds));
+        if let Some(session) = self.cache.get(profile) {
+            if session.remaining().filter(|d| *d > min_remaining).is_some() {
+                debug!("use cached session. profile:{}", profile.name());
+                return Ok(AssumeRoleResult {
+                    credentials: session.credentials,
+                    region_name: profile
+                        .region_name()
+                        .map(|s| s.to_string())
+                        .unwrap_or(session.region_name),
+                });
+            }
+        }
+
+        let result = self.assume_role.assume_role(profile, mfa_reader).await?;
+        let session = CachedSession {
+            key: Some(CacheKey::from(profile)),
+            region_name: result.region_name.clone(),
+            credentials: result.credentials.clone(),
+        };
+        self.cache.put(&session)?;
+        Ok(result)
+    }
+}
@@ -0,0 +1,139 @@
+use std::env;
+
+use crate::profile::Profile;
+
+/// The standard variables, which take precedence over the profile as AWS SDKs do.
+pub mod env_vars {
+    pub const ENDPOINT_URL_STS: &str = "AWS_ENDPOINT_URL_STS";
+    pub const ENDPOINT_URL: &str = "AWS_ENDPOINT_URL";
+    pub const IGNORE_CONFIGURED_ENDPOINT_URLS: &str = "AWS_IGNORE_CONFIGURED_ENDPOINT_URLS";
+    pub const STS_REGIONAL_ENDPOINTS: &str = "AWS_STS_REGIONAL_ENDPOINTS";
+    pub const USE_FIPS_ENDPOINT: &str = "AWS_USE_FIPS_ENDPOINT";
+    pub const USE_DUALSTACK_ENDPOINT: &str = "AWS_USE_DUALSTACK_ENDPOINT";
+}
+
+const GLOBAL_ENDPOINT: &str = "https://sts.amazonaws.com";
+
+/// The regions which call the global endpoint with `sts_regional_endpoints = legacy`.
+const LEGACY_GLOBAL_REGIONS: &[&str] = &[
+    "ap-northeast-1",
+    "ap-south-1",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ca-central-1",
+    "eu-central-1",
+    "eu-north-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "sa-east-1",
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+];
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|s| !s.is_empty())
+}
+
+fn parse_bool(name: &str, value: &str) -> anyhow::Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(anyhow::anyhow!(
+            "expected true or false. name:{}, value:{}",
+            name,
+            value
+        )),
+    }
+}
+
+/// Where to call STS for the profile.
+#[derive(Debug, Clone, Default)]
+pub struct StsEndpoint {
+    pub endpoint_url: Option<String>,
+    pub use_fips: bool,
+    pub use_dual_stack: bool,
+}
+
+impl StsEndpoint {
+    /// `endpoint_url` from the command line takes precedence over the environment, then the profile.
+    /// `region` is the region of the STS client.
+    pub fn resolve(
+        profile: &Profile,
+        endpoint_url: Option<&str>,
+        region: &str,
+    ) -> anyhow::Result<Self> {
+        let settings = &profile.endpoint;
+        let profile_bool = |name: &str, value: Option<&str>| match value {
+            Some(value) => parse_bool(name, value)
+                .map_err(|e| anyhow::anyhow!("{}, profile:{}", e, profile.name())),
+            None => Ok(false),
+        };
+        let use_fips = match var(env_vars::USE_FIPS_ENDPOINT) {
+            Some(value) => parse_bool(env_vars::USE_FIPS_ENDPOINT, &value)?,
+            None => profile_bool("use_fips_endpoint", settings.use_fips_endpoint.as_deref())?,
+        };
+        let use_dual_stack = match var(env_vars::USE_DUALSTACK_ENDPOINT) {
+            Some(value) => parse_bool(env_vars::USE_DUALSTACK_ENDPOINT, &value)?,
+            None => profile_bool(
+                "use_dualstack_endpoint",
+                settings.use_dualstack_endpoint.as_deref(),
+            )?,
+        };
+
+        let ignore_configured = match var(env_vars::IGNORE_CONFIGURED_ENDPOINT_URLS) {
+            Some(value) => parse_bool(env_vars::IGNORE_CONFIGURED_ENDPOINT_URLS, &value)?,
+            None => profile_bool(
+                "ignore_configured_endpoint_urls",
+                settings.ignore_configured_endpoint_urls.as_deref(),
+            )?,
+        };
+        let configured = || {
+            var(env_vars::ENDPOINT_URL_STS)
+                .or_else(|| var(env_vars::ENDPOINT_URL))
+                .or_else(|| settings.endpoint_url.clone())
+        };
+        let mut endpoint_url = endpoint_url
+            .map(|s| s.to_string())
+            .or_else(|| (!ignore_configured).then(configured).flatten());
+        if let Some(url) = endpoint_url.as_deref() {
+            reqwest::Url::parse(url).map_err(|e| {
+                anyhow::anyhow!(
+                    "invalid endpoint URL. profile:{}, endpoint_url:{}, error:{}",
+                    profile.name(),
+                    url,
+                    e
+                )
+            })?;
+        }
+
+        let regional_endpoints = var(env_vars::STS_REGIONAL_ENDPOINTS)
+            .or_else(|| settings.sts_regional_endpoints.clone());
+        match regional_endpoints.as_deref() {
+            None | Some("regional") => {}
+            // the global endpoint has neither FIPS nor dual-stack variants.
+            Some("legacy") => {
+                if endpoint_url.is_none()
+                    && !use_fips
+                    && !use_dual_stack
+                    && LEGACY_GLOBAL_REGIONS.contains(&region)
+                {
+                    endpoint_url = Some(GLOBAL_ENDPOINT.to_string());
+                }
+            }
+            Some(value) => anyhow::bail!(
+                "sts_regional_endpoints must be regional or legacy. profile:{}, value:{}",
+                profile.name(),
+                value
+            ),
+        }
+
+        Ok(StsEndpoint {
+            endpoint_url,
+            use_fips,
+            use_dual_stack,
+        })
+    }
+}
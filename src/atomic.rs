@@ -0,0 +1,47 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::Context;
+
+/// The mode of the existing file, or `mode` for a new one.
+pub fn mode_of(path: &Path, mode: u32) -> u32 {
+    fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o7777)
+        .unwrap_or(mode)
+}
+
+/// Writes a temporary file next to the file and renames it, so readers never see it half-written.
+/// The temporary file is created with `mode` before any byte is written, and a symlink is written through to its target.
+pub fn write(path: &Path, contents: &[u8], mode: u32) -> anyhow::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&dir)?;
+
+    let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("file");
+    // another process may write the same file at the same time.
+    let temp = dir.join(format!(".{}.{}.tmp", file_name, process::id()));
+    let _ = fs::remove_file(&temp);
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(&temp)
+        .and_then(|mut file| {
+            // the mode of `open` is masked by umask.
+            file.set_permissions(fs::Permissions::from_mode(mode))?;
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, &path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e).with_context(|| format!("cannot write {}", path.display()));
+    }
+    Ok(())
+}
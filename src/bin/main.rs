@@ -1,14 +1,19 @@
 use tracing::error;
 
 use assume_rolers::app::{self, App};
+use assume_rolers::config::Config;
+use assume_rolers::profile::files::ProfileFiles;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
-    let cmd = app::app().await?;
+    // a broken user config shouldn't prevent `--help`, `--version` and `lint`.
+    let config = Config::load();
+    let files = ProfileFiles::from_env().with_args(std::env::args_os());
+    let cmd = app::app(config.as_ref().ok(), &files).await?;
     let app = App::from(cmd);
-    match app.run().await {
+    match app.run(config, files).await {
         Ok(_) => Ok(()), // never
         Err(e) => {
             error!("error:{:?}", e);
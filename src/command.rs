@@ -1,6 +1,12 @@
+use std::collections::BTreeMap;
+
 use assume_rolers_schema::credentials::ProfileCredentials;
+use assume_rolers_schema::variables::{Variable, VariableMapping};
 use async_trait::async_trait;
 
+pub mod direnv;
+pub mod exec;
+pub mod export;
 pub mod federation;
 pub mod shell;
 pub mod wasm;
@@ -10,32 +16,46 @@ pub trait Command {
     async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()>;
 }
 
-struct Variable<'a> {
-    name: &'a str,
-    value: Option<String>,
+/// Quotes the value for POSIX shells, since the values of `env` in the config can be anything.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r#"'\''"#))
+}
+
+/// Quotes the value for fish, which unescapes `\\` and `\'` in single quotes.
+pub fn quote_fish(s: &str) -> String {
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
 }
 
-fn into_variables(request: &ProfileCredentials) -> Vec<Variable> {
-    fn v<S: Into<String>>(name: &str, value: Option<S>) -> Variable {
-        Variable {
-            name,
-            value: value.map(|s| s.into()),
+/// Environment variables to export for the assumed profile.
+#[derive(Debug, Clone, Default)]
+pub struct Exports {
+    mapping: VariableMapping,
+    profile_env: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Exports {
+    pub fn new(
+        mapping: VariableMapping,
+        profile_env: BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Exports {
+        Exports {
+            mapping,
+            profile_env,
         }
     }
 
-    vec![
-        // for AWS SDK, aws-cli
-        v("AWS_PROFILE", Option::<String>::None),
-        v("AWS_REGION", Some(request.region_name.as_str())),
-        v("AWS_DEFAULT_REGION", Some(request.region_name.as_str())),
-        v("AWS_ACCESS_KEY_ID", Some(request.credentials.key())),
-        v("AWS_SECRET_ACCESS_KEY", Some(request.credentials.secret())),
-        v("AWS_SESSION_TOKEN", request.credentials.token()),
-        v(
-            "AWS_SESSION_EXPIRATION",
-            request.credentials.expires_at.map(|dt| dt.to_rfc3339()),
-        ),
-        // for prompts
-        v("ASSUME_ROLERS_PROFILE", Some(request.profile_name.as_str())),
-    ]
+    pub fn variables(&self, credentials: &ProfileCredentials) -> Vec<Variable> {
+        let extra = self
+            .profile_env
+            .get(&credentials.profile_name)
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| Variable::set(name, value));
+
+        self.mapping
+            .variables(credentials)
+            .into_iter()
+            .chain(extra)
+            .collect()
+    }
 }
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use assume_rolers_schema::credentials::ProfileCredentials;
+
+use crate::command::{quote, Command, Exports};
+
+/// Prints the variables in the form `direnv` can evaluate from `.envrc`.
+pub struct DirenvCommand {
+    exports: Exports,
+}
+
+impl DirenvCommand {
+    pub fn new(exports: Exports) -> DirenvCommand {
+        DirenvCommand { exports }
+    }
+}
+
+#[async_trait]
+impl Command for DirenvCommand {
+    async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()> {
+        for variable in self.exports.variables(&credentials) {
+            if let Some(value) = variable.value {
+                println!("export {}={}", variable.name, quote(&value));
+            } else {
+                println!("unset {}", variable.name);
+            }
+        }
+
+        Ok(())
+    }
+}
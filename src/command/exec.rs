@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+
+use assume_rolers_schema::credentials::ProfileCredentials;
+
+use crate::command::{Command, Exports};
+
+/// Runs a command with the variables, and waits for it.
+#[derive(Clone)]
+pub struct ExecCommand {
+    program: String,
+    args: Vec<String>,
+    exports: Exports,
+}
+
+impl ExecCommand {
+    pub fn new(program: String, args: Vec<String>, exports: Exports) -> ExecCommand {
+        ExecCommand {
+            program,
+            args,
+            exports,
+        }
+    }
+}
+
+#[async_trait]
+impl Command for ExecCommand {
+    async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()> {
+        let mut command = tokio::process::Command::new(&self.program);
+        command.args(&self.args);
+        for variable in self.exports.variables(&credentials) {
+            if let Some(value) = variable.value {
+                command.env(variable.name, value);
+            } else {
+                command.env_remove(variable.name);
+            }
+        }
+
+        let status = command.status().await?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "command failed. profile:{}, command:{}, status:{}",
+                credentials.profile_name,
+                self.program,
+                status
+            ))
+        }
+    }
+}
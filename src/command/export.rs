@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use std::env;
+
+use assume_rolers_schema::credentials::ProfileCredentials;
+use assume_rolers_schema::shell::Shell;
+
+use crate::command::{quote, quote_fish, Command, Exports};
+
+/// The builtin `export` plugin. It's rendered on the host, so it shares the variables with the shell session.
+#[derive(Clone)]
+pub struct ExportCommand {
+    exports: Exports,
+}
+
+impl ExportCommand {
+    pub fn new(exports: Exports) -> ExportCommand {
+        ExportCommand { exports }
+    }
+}
+
+#[async_trait]
+impl Command for ExportCommand {
+    async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()> {
+        let shell = Shell::from_process_path(env::var("SHELL")?);
+        let Some(shell) = shell else {
+            return Ok(());
+        };
+        for variable in self.exports.variables(&credentials) {
+            match (&shell, variable.value) {
+                (Shell::Bash | Shell::Zsh, Some(value)) => {
+                    println!("export {}={}", variable.name, quote(&value))
+                }
+                (Shell::Bash | Shell::Zsh, None) => println!("unset {}", variable.name),
+                (Shell::Fish, Some(value)) => {
+                    println!("set -gx {} {}", variable.name, quote_fish(&value))
+                }
+                (Shell::Fish, None) => println!("set -e {}", variable.name),
+                (Shell::Unknown(s), _) => anyhow::bail!("unsupported shell. shell:{}", s),
+            }
+        }
+
+        Ok(())
+    }
+}
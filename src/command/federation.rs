@@ -4,14 +4,22 @@ use reqwest::Url;
 use serde::{Deserialize, Serialize};
 
 use crate::command::Command;
+use crate::network::NetworkSettings;
+use crate::partition::Partition;
+use crate::profile::arn::RoleArn;
 
 use assume_rolers_schema::credentials::{Credentials, ProfileCredentials};
 
-mod endpoints {
-    pub const FEDERATION: &str = "https://signin.aws.amazon.com/federation";
+#[derive(Clone)]
+pub struct FederationCommand {
+    network: NetworkSettings,
 }
 
-pub struct FederationCommand;
+impl FederationCommand {
+    pub fn new(network: NetworkSettings) -> Self {
+        FederationCommand { network }
+    }
+}
 
 #[async_trait]
 impl Command for FederationCommand {
@@ -21,10 +29,22 @@ impl Command for FederationCommand {
             .expires_at
             .ok_or_else(|| anyhow::anyhow!("expires_at is missing."))?;
 
+        // the role decides the partition. the region does for the other profiles.
+        let partition = match credentials.role_arn.as_deref() {
+            Some(role_arn) => role_arn.parse::<RoleArn>()?.partition,
+            None => Partition::from_region(&credentials.region_name),
+        };
         let session = FederatedSession::try_from(credentials.credentials)?;
         let session_duration = expires_at - Utc::now();
 
-        let client = FederationClient;
+        let network = self
+            .network
+            .with_profile_ca_bundle(credentials.ca_bundle.as_deref());
+        let client = FederationClient {
+            partition,
+            client: network.client()?,
+            network,
+        };
         let signin_token = client.signin_token(session, session_duration).await?;
         let url = client.signin_url(signin_token)?;
 
@@ -72,7 +92,11 @@ impl TryFrom<Credentials> for FederatedSession {
     }
 }
 
-struct FederationClient;
+struct FederationClient {
+    partition: Partition,
+    client: reqwest::Client,
+    network: NetworkSettings,
+}
 
 impl FederationClient {
     pub async fn signin_token(
@@ -88,10 +112,16 @@ impl FederationClient {
             ("Session", session),
         ];
 
-        let client = reqwest::Client::new();
-        let signin_endpoint = endpoints::FEDERATION.parse::<Url>()?;
-        let response = client.get(signin_endpoint).query(&query).send().await?;
-        let response = serde_json::from_str::<FederatedResponse>(&response.text().await?)?;
+        let signin_endpoint = self.partition.federation_endpoint().parse::<Url>()?;
+        let response = self
+            .client
+            .get(signin_endpoint)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| self.network.error(e))?;
+        let text = response.text().await.map_err(|e| self.network.error(e))?;
+        let response = serde_json::from_str::<FederatedResponse>(&text)?;
         Ok(response.signin_token)
     }
 
@@ -99,11 +129,11 @@ impl FederationClient {
         let query = [
             ("Action", "login".to_string()),
             ("Issuer", "".to_string()),
-            ("Destination", "https://console.aws.amazon.com/".to_string()),
+            ("Destination", self.partition.console_url().to_string()),
             ("SigninToken", signin_token.0),
         ];
 
-        let url = Url::parse_with_params(endpoints::FEDERATION, query)?;
+        let url = Url::parse_with_params(self.partition.federation_endpoint(), query)?;
         Ok(url)
     }
 }
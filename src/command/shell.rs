@@ -5,22 +5,31 @@ use std::ffi::CString;
 use tracing::debug;
 
 use assume_rolers_schema::credentials::ProfileCredentials;
+use assume_rolers_schema::variables::Variable;
 
-use crate::command::{into_variables, Command, Variable};
+use crate::command::{Command, Exports};
 
-pub struct ShellCommand;
+#[derive(Clone)]
+pub struct ShellCommand {
+    exports: Exports,
+}
+
+impl ShellCommand {
+    pub fn new(exports: Exports) -> ShellCommand {
+        ShellCommand { exports }
+    }
+}
 
 #[async_trait]
 impl Command for ShellCommand {
     async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()> {
-        set_credentials(credentials);
+        set_variables(self.exports.variables(&credentials));
         start_shell_session()?;
         Ok(())
     }
 }
 
-fn set_credentials(credentials: ProfileCredentials) {
-    let variables = into_variables(&credentials);
+fn set_variables(variables: Vec<Variable>) {
     for Variable { name, value } in variables {
         if let Some(value) = value {
             env::set_var(name, value);
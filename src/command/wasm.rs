@@ -11,44 +11,30 @@ use assume_rolers_schema::credentials::ProfileCredentials;
 use assume_rolers_schema::plugin::PluginPayload;
 use assume_rolers_schema::shell::Shell;
 
-use crate::command::Command;
-
-enum WasmModule {
-    File(PathBuf),
-    Binary(String, Vec<u8>),
-}
-
-impl WasmModule {
-    fn name(&self) -> String {
-        match self {
-            WasmModule::File(path) => {
-                format!(
-                    "assume-rolers-plugin-{}",
-                    path.file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                )
-            }
-            WasmModule::Binary(name_, _) => name_.to_string(),
-        }
-    }
-}
+use crate::command::{Command, Exports};
 
+#[derive(Clone)]
 pub struct WasmCommand {
-    module: WasmModule,
+    path: PathBuf,
+    exports: Exports,
 }
 
 impl WasmCommand {
-    pub fn from_file<P: AsRef<Path>>(wasm_path: P) -> WasmCommand {
+    pub fn from_file<P: AsRef<Path>>(wasm_path: P, exports: Exports) -> WasmCommand {
         WasmCommand {
-            module: WasmModule::File(wasm_path.as_ref().to_path_buf()),
+            path: wasm_path.as_ref().to_path_buf(),
+            exports,
         }
     }
 
-    pub fn from_binary(name: &str, binary: Vec<u8>) -> WasmCommand {
-        WasmCommand {
-            module: WasmModule::Binary(name.to_string(), binary),
-        }
+    fn name(&self) -> String {
+        format!(
+            "assume-rolers-plugin-{}",
+            self.path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+        )
     }
 }
 
@@ -56,7 +42,8 @@ impl WasmCommand {
 impl Command for WasmCommand {
     async fn run(self, credentials: ProfileCredentials) -> anyhow::Result<()> {
         let shell = Shell::from_process_path(&env::var("SHELL")?);
-        let payload = PluginPayload::new(shell, credentials);
+        let variables = self.exports.variables(&credentials);
+        let payload = PluginPayload::new(shell, credentials, variables);
         let input = serde_json::to_string(&payload)?;
         let stdin = Box::new(ReadPipe::from(input));
 
@@ -67,14 +54,11 @@ impl Command for WasmCommand {
         let wasi = WasiCtxBuilder::new().stdin(stdin).inherit_stdout().build();
         let mut store = Store::new(&engine, wasi);
 
-        let module = match &self.module {
-            WasmModule::File(path) => Module::from_file(&engine, path)?,
-            WasmModule::Binary(_, binary) => Module::from_binary(&engine, binary)?,
-        };
-        linker.module(&mut store, &self.module.name(), &module)?;
+        let module = Module::from_file(&engine, &self.path)?;
+        linker.module(&mut store, &self.name(), &module)?;
 
         let r = linker
-            .get_default(&mut store, &self.module.name())?
+            .get_default(&mut store, &self.name())?
             .typed::<(), (), _>(&store)?
             .call(&mut store, ());
         match r {
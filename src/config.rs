@@ -0,0 +1,376 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use assume_rolers_schema::variables::VariableMapping;
+
+use crate::command::Exports;
+use crate::dirs;
+use crate::profile::arn::is_account_id;
+use crate::profile::Profile;
+use crate::region;
+
+pub mod cli;
+
+pub mod env_vars {
+    pub const CONFIG: &str = "ASSUME_ROLERS_CONFIG";
+    pub const CONFIG_FILES: &str = "ASSUME_ROLERS_CONFIG_FILES";
+    pub const PLUGIN: &str = "ASSUME_ROLERS_PLUGIN";
+    pub const REGION: &str = "ASSUME_ROLERS_REGION";
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    /// The region exported when neither the command line, the environment nor the profiles specify one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_region: Option<String>,
+    pub selector: SelectorConfig,
+    pub cache: CacheConfig,
+    pub mfa: MfaConfig,
+    pub network: NetworkConfig,
+    pub variables: VariableMapping,
+    pub aliases: BTreeMap<String, String>,
+    pub groups: BTreeMap<String, Vec<String>>,
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    #[serde(alias = "template")]
+    pub templates: BTreeMap<String, TemplateConfig>,
+
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectorConfig {
+    pub reverse: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// One of `default`, `reverse` or `reverse-list`. Takes precedence over `reverse`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<String>,
+    /// Key bindings in skim's `--bind` format, e.g. `ctrl-k:kill-line`.
+    pub bind: Vec<String>,
+    pub preview: bool,
+    /// Profiles pinned to the top of the list. Others are sorted by frecency.
+    pub favorites: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_window: Option<String>,
+    pub environments: Vec<EnvironmentConfig>,
+    /// Delegates the selection to an external fuzzy finder instead of skim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finder: Option<FinderConfig>,
+}
+
+impl Default for SelectorConfig {
+    fn default() -> Self {
+        SelectorConfig {
+            reverse: true,
+            prompt: None,
+            layout: None,
+            height: None,
+            bind: Vec::default(),
+            preview: true,
+            favorites: Vec::default(),
+            preview_window: None,
+            environments: vec![EnvironmentConfig {
+                name: "prod".to_string(),
+                pattern: Some("prod".to_string()),
+                accounts: Vec::default(),
+                color: Some("red".to_string()),
+            }],
+            finder: None,
+        }
+    }
+}
+
+/// A command such as `fzf`, `sk`, `rofi -dmenu` or `dmenu`, run by `sh -c`.
+/// It reads the profile names from stdin, and writes the selected ones to stdout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FinderConfig {
+    pub command: String,
+    /// Used to select multiple profiles, e.g. `fzf --multi`. Defaults to `command`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_command: Option<String>,
+}
+
+/// Labels the profiles matching the pattern or the accounts, e.g. `prod`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentConfig {
+    pub name: String,
+    /// A regular expression matched against the profile name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// A color name such as `red`, or an ANSI 256-color number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl EnvironmentConfig {
+    pub fn matches(&self, profile: &Profile) -> bool {
+        let by_pattern = self
+            .pattern
+            .as_ref()
+            .and_then(|p| Regex::new(p).ok())
+            .map(|re| re.is_match(profile.name()))
+            .unwrap_or(false);
+        let by_account = profile
+            .role()
+            .map(|arn| self.accounts.contains(&arn.account_id))
+            .unwrap_or(false);
+        by_pattern || by_account
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Cached sessions expiring within this period are assumed again.
+    pub min_remaining_seconds: u32,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: false,
+            min_remaining_seconds: 300,
+        }
+    }
+}
+
+/// How to call AWS. Proxies are taken from `HTTPS_PROXY` and `NO_PROXY`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// The PEM file of the CA certificates to trust in addition to the system's.
+    /// `AWS_CA_BUNDLE` and `ca_bundle` in the profile take precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+    pub connect_timeout_seconds: u64,
+    /// The time limit of a call, including the retries of AWS SDK.
+    pub timeout_seconds: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            ca_bundle: None,
+            connect_timeout_seconds: 10,
+            timeout_seconds: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MfaProvider {
+    #[default]
+    Stdin,
+    Command,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MfaConfig {
+    pub provider: MfaProvider,
+    /// A shell command printing the token code. `ASSUME_ROLERS_MFA_SERIAL` is available in it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_session_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa_serial: Option<String>,
+    /// Builds the profile from the template instead of the AWS config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The account to assume the role of the template in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    pub env: BTreeMap<String, String>,
+    /// Labels for filtering, e.g. `env = "prod"`.
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Properties shared by the profiles which differ only in the account, e.g. `prod = { template = "admin", account = "111111111111" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateConfig {
+    /// The role name, with the path if any, e.g. `Admin` or `ops/Admin`.
+    pub role_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_session_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa_serial: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os(env_vars::CONFIG)
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("config.toml")))
+    }
+
+    pub fn load() -> anyhow::Result<Config> {
+        if let Some(path) = Self::default_path() {
+            Self::from_file(path)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Config> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e).with_context(|| format!("cannot read {}", path.display())),
+        };
+
+        let mut config = toml::from_str::<Config>(&text)
+            .with_context(|| format!("invalid config file. path:{}", path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("invalid config file. path:{}", path.display()))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        self.variables.validate()?;
+        for env in self.selector.environments.iter() {
+            if let Some(pattern) = env.pattern.as_ref() {
+                Regex::new(pattern).with_context(|| {
+                    format!(
+                        "invalid pattern in selector.environments. name:{}",
+                        env.name
+                    )
+                })?;
+            }
+        }
+        if let Some(default_region) = self.default_region.as_deref() {
+            region::validate(default_region).context("invalid default_region.")?;
+        }
+        if self.network.connect_timeout_seconds == 0 || self.network.timeout_seconds == 0 {
+            anyhow::bail!("network timeouts must be positive.");
+        }
+        if self.mfa.provider == MfaProvider::Command && self.mfa.command.is_none() {
+            anyhow::bail!("mfa.command is required for the command provider.");
+        }
+        for (name, profile) in self.profiles.iter() {
+            match (profile.template.as_ref(), profile.account.as_ref()) {
+                (Some(template), Some(account)) => {
+                    if !self.templates.contains_key(template) {
+                        anyhow::bail!(
+                            "No template found. profile_name:{}, template:{}",
+                            name,
+                            template
+                        );
+                    }
+                    if !is_account_id(account) {
+                        anyhow::bail!(
+                            "invalid account ID. profile_name:{}, account:{}",
+                            name,
+                            account
+                        );
+                    }
+                }
+                (None, None) => {}
+                _ => anyhow::bail!(
+                    "template and account must be specified together. profile_name:{}",
+                    name
+                ),
+            }
+        }
+        if let Some((alias, target)) = self
+            .aliases
+            .iter()
+            .find(|(_, target)| self.aliases.contains_key(target.as_str()))
+        {
+            anyhow::bail!(
+                "alias must point to a profile. alias:{}, target:{}",
+                alias,
+                target
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(|s| s.as_str()).unwrap_or(name)
+    }
+
+    pub fn aliases_of<'a>(&'a self, profile_name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.aliases
+            .iter()
+            .filter(move |(_, target)| target.as_str() == profile_name)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// Returns pairs of an alias and the profile name, for the existing profiles.
+    pub fn alias_pairs(&self, profile_names: &[String]) -> Vec<(String, String)> {
+        self.aliases
+            .iter()
+            .filter(|(_, target)| profile_names.contains(target))
+            .map(|(alias, target)| (alias.to_string(), target.to_string()))
+            .collect()
+    }
+
+    /// Returns the profile names in the group, with the aliases resolved.
+    pub fn group(&self, group_name: &str) -> Option<Vec<String>> {
+        self.groups.get(group_name).map(|members| {
+            members
+                .iter()
+                .map(|m| self.resolve_alias(m).to_string())
+                .collect()
+        })
+    }
+
+    pub fn profile(&self, profile_name: &str) -> Option<&ProfileConfig> {
+        self.profiles.get(profile_name)
+    }
+
+    pub fn exports(&self) -> Exports {
+        let profile_env = self
+            .profiles
+            .iter()
+            .filter(|(_, p)| !p.env.is_empty())
+            .map(|(name, p)| (name.to_string(), p.env.clone()))
+            .collect();
+        Exports::new(self.variables.clone(), profile_env)
+    }
+}
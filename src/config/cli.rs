@@ -0,0 +1,172 @@
+use clap::ArgAction;
+
+use crate::config::Config;
+use crate::profile::files::ProfileFiles;
+use crate::profile::load::aws_sdk::AwsSdkProfileLoader;
+use crate::profile::load::config::ConfigProfileLoader;
+use crate::profile::load::LoadProfiles;
+use crate::project::ProjectFile;
+use crate::settings::{Layer, Setting, Settings, Source};
+
+#[derive(Debug)]
+pub struct ShowConfig {
+    effective: bool,
+    profile: Option<String>,
+}
+
+impl ShowConfig {
+    pub fn command() -> clap::Command {
+        clap::Command::new("config")
+            .about("Show the configuration.")
+            .subcommand_required(true)
+            .subcommand(
+                clap::Command::new("show")
+                    .about("Show the user config, or the effective settings.")
+                    .arg(
+                        clap::Arg::new("effective")
+                            .long("effective")
+                            .action(ArgAction::SetTrue)
+                            .help("Show the effective settings and where they came from."),
+                    )
+                    .arg(
+                        clap::Arg::new("profile")
+                            .long("profile")
+                            .requires("effective")
+                            .value_hint(clap::ValueHint::Other)
+                            .help("Show the effective settings as if the profile is specified."),
+                    ),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        if !self.effective {
+            if let Some(path) = config.path.as_ref() {
+                println!("# {}", path.display());
+            }
+            print!("{}", toml::to_string_pretty(&config)?);
+            return Ok(());
+        }
+
+        fn show<T: std::fmt::Display>(name: &str, setting: Option<Setting<T>>) {
+            if let Some(setting) = setting {
+                println!("{:<20} = {}", name, setting);
+            } else {
+                println!("{:<20} = -", name);
+            }
+        }
+
+        let project = ProjectFile::discover_trusted()?;
+        let cli = Layer {
+            profile: self.profile,
+            ..Layer::default()
+        };
+        let settings = Settings::resolve(cli, project.as_ref().map(|p| &p.config), &config);
+        let default_region = settings.default_region.clone();
+
+        println!(
+            "# user config: {}",
+            config
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        );
+        println!(
+            "# project file: {}",
+            project
+                .as_ref()
+                .map(|p| p.path.display().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        );
+        show("profile", settings.profile.clone());
+        show("plugin", settings.plugin);
+        println!("{:<20} = {:?}", "mfa.provider", config.mfa.provider);
+        println!("{:<20} = {}", "cache.enabled", config.cache.enabled);
+
+        if let Some(profile) = settings.profile {
+            // profiles built from templates come from the user config as a whole.
+            let mut profiles = AwsSdkProfileLoader::from(&files).load_profiles().await?;
+            let mut base_source = Source::AwsConfig;
+            if profiles.get_profile(&profile.value).is_none() {
+                profiles =
+                    ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone())
+                        .load_profiles()
+                        .await?;
+                base_source = Source::UserConfig;
+            }
+            let aws = profiles.get_profile(&profile.value).ok_or_else(|| {
+                anyhow::anyhow!("No profile found. profile_name:{}", profile.value)
+            })?;
+            let overrides = config.profile(&profile.value);
+
+            let layered = |user: Option<String>, aws: Option<String>| {
+                user.map(|value| Setting {
+                    value,
+                    source: Source::UserConfig,
+                })
+                .or_else(|| {
+                    aws.map(|value| Setting {
+                        value,
+                        source: base_source,
+                    })
+                })
+            };
+
+            println!();
+            println!("[profile {}]", profile.value);
+            show(
+                "region",
+                settings
+                    .region
+                    .or_else(|| {
+                        layered(
+                            overrides.and_then(|o| o.region.clone()),
+                            profiles
+                                .source_chain(aws.name())
+                                .iter()
+                                .find_map(|p| p.region_name())
+                                .map(|s| s.to_string()),
+                        )
+                    })
+                    .or_else(|| default_region.clone()),
+            );
+            show(
+                "duration_seconds",
+                layered(
+                    overrides.and_then(|o| o.duration_seconds.map(|n| n.to_string())),
+                    aws.duration_seconds.map(|n| n.to_string()),
+                ),
+            );
+            show(
+                "role_session_name",
+                layered(
+                    overrides.and_then(|o| o.role_session_name.clone()),
+                    aws.role_session_name.clone(),
+                ),
+            );
+            show(
+                "mfa_serial",
+                layered(
+                    overrides.and_then(|o| o.mfa_serial.clone()),
+                    aws.mfa_serial.clone(),
+                ),
+            );
+        } else {
+            show("region", settings.region.or(default_region));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for ShowConfig {
+    fn from(m: &clap::ArgMatches) -> Self {
+        let m = m
+            .subcommand_matches("show")
+            .expect("subcommand is required");
+        ShowConfig {
+            effective: m.get_flag("effective"),
+            profile: m.get_one::<String>("profile").map(|s| s.to_string()),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_NAME: &str = "assume-rolers";
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .map(|dir| dir.join(APP_NAME))
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+fn aws_file(var: &str, name: &str) -> Option<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    match env::var(var).ok().filter(|s| !s.is_empty()) {
+        Some(path) => match (path.strip_prefix("~/"), home) {
+            (Some(rest), Some(home)) => Some(home.join(rest)),
+            _ => Some(PathBuf::from(path)),
+        },
+        None => home.map(|home| home.join(".aws").join(name)),
+    }
+}
+
+/// The shared AWS config file, `AWS_CONFIG_FILE` or `~/.aws/config`.
+pub fn aws_config_file() -> Option<PathBuf> {
+    aws_file("AWS_CONFIG_FILE", "config")
+}
+
+/// The shared AWS credentials file, `AWS_SHARED_CREDENTIALS_FILE` or `~/.aws/credentials`.
+pub fn aws_credentials_file() -> Option<PathBuf> {
+    aws_file("AWS_SHARED_CREDENTIALS_FILE", "credentials")
+}
+
+/// Where the AWS CLI caches the SSO tokens, `~/.aws/sso/cache`.
+pub fn aws_sso_cache_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".aws").join("sso").join("cache"))
+}
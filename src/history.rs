@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+use crate::dirs;
+
+pub mod cli;
+
+const MAX_ENTRIES: usize = 1000;
+
+/// A successful assumption. Never contains the credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub profile_name: String,
+    /// The role and its source profile, to build a dynamic role again for `-`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_arn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_profile: Option<String>,
+    pub region_name: String,
+    pub assumed_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The history is a convenience, so a broken or unreadable one is ignored rather than failing to assume.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    /// None if the data directory is unknown, which keeps the history in memory.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn load() -> History {
+        match dirs::data_dir() {
+            Some(dir) => Self::from_file(dir.join("history.json")),
+            None => History::default(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> History {
+        let path = path.as_ref();
+        let entries = match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<History>(&json) {
+                Ok(history) => history.entries,
+                Err(e) => {
+                    eprintln!(
+                        "ignored the broken history. path:{}, error:{}",
+                        path.display(),
+                        e
+                    );
+                    Vec::default()
+                }
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::default(),
+            Err(e) => {
+                eprintln!(
+                    "ignored the unreadable history. path:{}, error:{}",
+                    path.display(),
+                    e
+                );
+                Vec::default()
+            }
+        };
+        History {
+            path: Some(path.to_path_buf()),
+            entries,
+        }
+    }
+
+    pub fn record(&mut self, entry: HistoryEntry) -> anyhow::Result<()> {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.save()
+    }
+
+    /// Replaces the file atomically, since other shells may read or write it at the same time.
+    fn save(&self) -> anyhow::Result<()> {
+        if let Some(path) = self.path.as_ref() {
+            let json = serde_json::to_string(self)?;
+            atomic::write(path, json.as_bytes(), atomic::mode_of(path, 0o600))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the entries from the newest.
+    pub fn recent(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn last(&self) -> Option<&HistoryEntry> {
+        self.entries.last()
+    }
+
+    /// Scores the profiles by how often and how recently they were assumed.
+    pub fn frecency(&self) -> HashMap<String, u32> {
+        let now = Utc::now();
+        let mut scores = HashMap::<String, u32>::new();
+        for entry in self.entries.iter() {
+            let age = now - entry.assumed_at;
+            let weight = if age < Duration::hours(4) {
+                100
+            } else if age < Duration::days(1) {
+                80
+            } else if age < Duration::weeks(1) {
+                60
+            } else if age < Duration::days(30) {
+                40
+            } else if age < Duration::days(90) {
+                20
+            } else {
+                10
+            };
+            *scores.entry(entry.profile_name.to_string()).or_default() += weight;
+        }
+        scores
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{Local, Utc};
+
+use crate::history::History;
+
+#[derive(Debug)]
+pub struct ShowHistory {
+    limit: usize,
+}
+
+impl ShowHistory {
+    pub fn command() -> clap::Command {
+        clap::Command::new("history")
+            .about("Show recently assumed profiles.")
+            .arg(
+                clap::Arg::new("limit")
+                    .short('n')
+                    .long("limit")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("20")
+                    .help("Specify the number of entries to show."),
+            )
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let history = History::load();
+        let now = Utc::now();
+        for entry in history.recent().take(self.limit) {
+            let status = match entry.expires_at {
+                Some(expires_at) if expires_at > now => {
+                    format!("{}m remaining", (expires_at - now).num_minutes())
+                }
+                Some(_) => "expired".to_string(),
+                None => "-".to_string(),
+            };
+            println!(
+                "{}  {:<40}  {:<16}  {}",
+                entry
+                    .assumed_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                entry.profile_name,
+                entry.region_name,
+                status
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for ShowHistory {
+    fn from(m: &clap::ArgMatches) -> Self {
+        ShowHistory {
+            limit: *m.get_one::<usize>("limit").expect("default value exists"),
+        }
+    }
+}
@@ -1,6 +1,18 @@
 pub mod app;
 pub mod assume_role;
+mod atomic;
 pub mod command;
+pub mod config;
+mod dirs;
+pub mod history;
 pub mod mfa;
+pub mod network;
+pub mod organizations;
+pub mod output;
+pub mod partition;
 pub mod profile;
+pub mod project;
+pub mod region;
 pub mod run;
+pub mod settings;
+pub mod sso;
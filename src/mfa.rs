@@ -1,8 +1,11 @@
 use std::io;
 use std::io::Write;
+use std::process::Stdio;
 
 use async_trait::async_trait;
 
+use crate::config::{MfaConfig, MfaProvider};
+
 #[async_trait]
 pub trait ReadMfaToken: Clone {
     async fn read_mfa_token(&self, mfa_serial: &str) -> anyhow::Result<String>;
@@ -40,3 +43,73 @@ impl ReadMfaToken for StaticMfaTokenReader {
         Ok(self.token.clone())
     }
 }
+
+/// Reads a token code from the output of a shell command, e.g. `ykman oath accounts code -s aws`.
+#[derive(Clone)]
+pub struct CommandMfaTokenReader {
+    command: String,
+}
+
+impl<S: Into<String>> From<S> for CommandMfaTokenReader {
+    fn from(s: S) -> Self {
+        CommandMfaTokenReader { command: s.into() }
+    }
+}
+
+#[async_trait]
+impl ReadMfaToken for CommandMfaTokenReader {
+    async fn read_mfa_token(&self, mfa_serial: &str) -> anyhow::Result<String> {
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("ASSUME_ROLERS_MFA_SERIAL", mfa_serial)
+            .stderr(Stdio::inherit())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "MFA command failed. command:{}, status:{}",
+                self.command,
+                output.status
+            );
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+}
+
+/// The reader chosen by the command line and the config.
+#[derive(Clone)]
+pub enum MfaReader {
+    Stdin(StdinMfaTokenReader),
+    Static(StaticMfaTokenReader),
+    Command(CommandMfaTokenReader),
+}
+
+impl MfaReader {
+    /// `token` is the token code given on the command line, which takes precedence over the config.
+    pub fn new(token: Option<&String>, config: &MfaConfig) -> Self {
+        if let Some(token) = token {
+            MfaReader::Static(StaticMfaTokenReader::from(token))
+        } else if let (MfaProvider::Command, Some(command)) =
+            (config.provider, config.command.as_ref())
+        {
+            MfaReader::Command(CommandMfaTokenReader::from(command))
+        } else {
+            MfaReader::Stdin(StdinMfaTokenReader)
+        }
+    }
+}
+
+#[async_trait]
+impl ReadMfaToken for MfaReader {
+    async fn read_mfa_token(&self, mfa_serial: &str) -> anyhow::Result<String> {
+        use MfaReader::*;
+        match self {
+            Stdin(r) => r.read_mfa_token(mfa_serial).await,
+            Static(r) => r.read_mfa_token(mfa_serial).await,
+            Command(r) => r.read_mfa_token(mfa_serial).await,
+        }
+    }
+}
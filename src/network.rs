@@ -0,0 +1,237 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use aws_config::timeout::TimeoutConfig;
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpClient,
+    SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+use aws_smithy_runtime_api::client::result::{ConnectorError, SdkError};
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::body::SdkBody;
+use reqwest::Certificate;
+
+use crate::config::NetworkConfig;
+
+/// The standard variable of the CA bundle, which takes precedence over the profiles as AWS SDKs do.
+pub mod env_vars {
+    pub const CA_BUNDLE: &str = "AWS_CA_BUNDLE";
+}
+
+/// How every client calls AWS, i.e. STS, SSO, Organizations and the federation endpoint.
+/// Proxies are taken from `HTTPS_PROXY` and `NO_PROXY` by reqwest.
+#[derive(Debug, Clone)]
+pub struct NetworkSettings {
+    ca_bundle: Option<PathBuf>,
+    profile_ca_bundle: Option<PathBuf>,
+    connect_timeout: Duration,
+    timeout: Duration,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings::from(&NetworkConfig::default())
+    }
+}
+
+impl From<&NetworkConfig> for NetworkSettings {
+    fn from(config: &NetworkConfig) -> Self {
+        NetworkSettings {
+            ca_bundle: config.ca_bundle.clone(),
+            profile_ca_bundle: None,
+            connect_timeout: Duration::from_secs(config.connect_timeout_seconds),
+            timeout: Duration::from_secs(config.timeout_seconds),
+        }
+    }
+}
+
+impl NetworkSettings {
+    /// `ca_bundle` of the profile, which takes precedence over the user config.
+    pub fn with_profile_ca_bundle(mut self, ca_bundle: Option<&str>) -> Self {
+        self.profile_ca_bundle = ca_bundle.map(PathBuf::from);
+        self
+    }
+
+    /// `AWS_CA_BUNDLE` takes precedence over the profile, then the user config.
+    fn ca_bundle(&self) -> Option<PathBuf> {
+        env::var_os(env_vars::CA_BUNDLE)
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| self.profile_ca_bundle.clone())
+            .or_else(|| self.ca_bundle.clone())
+    }
+
+    pub fn client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout);
+        if let Some(path) = self.ca_bundle() {
+            for certificate in read_certificates(&path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(builder.build()?)
+    }
+
+    /// The HTTP client for AWS SDK, which sends the requests with the same client as the others.
+    pub fn sdk_client(&self) -> anyhow::Result<SharedHttpClient> {
+        Ok(SharedHttpClient::new(SdkHttpClient {
+            client: self.client()?,
+            settings: self.clone(),
+        }))
+    }
+
+    /// The timeouts of AWS SDK. The operation timeout covers the retries.
+    pub fn timeout_config(&self) -> TimeoutConfig {
+        TimeoutConfig::builder()
+            .connect_timeout(self.connect_timeout)
+            .operation_timeout(self.timeout)
+            .build()
+    }
+
+    /// Describes timeouts with the setting to raise. Other errors are kept as they are.
+    pub fn error(&self, e: reqwest::Error) -> anyhow::Error {
+        let url = e.url().map(|url| url.as_str()).unwrap_or_default();
+        if e.is_timeout() && e.is_connect() {
+            anyhow::anyhow!(
+                "timed out connecting to {}. check the proxy or raise network.connect_timeout_seconds. connect_timeout_seconds:{}",
+                url,
+                self.connect_timeout.as_secs()
+            )
+        } else if e.is_timeout() {
+            anyhow::anyhow!(
+                "timed out calling {}. raise network.timeout_seconds. timeout_seconds:{}",
+                url,
+                self.timeout.as_secs()
+            )
+        } else {
+            e.into()
+        }
+    }
+
+    /// Names the setting to raise if the operation of AWS SDK timed out. Other errors are kept as they are.
+    pub fn sdk_error<E, R>(&self, service: &str, e: SdkError<E, R>) -> anyhow::Error
+    where
+        SdkError<E, R>: std::error::Error + Send + Sync + 'static,
+    {
+        if let SdkError::TimeoutError(_) = e {
+            anyhow::Error::new(e).context(format!(
+                "timed out calling {}. raise network.timeout_seconds. timeout_seconds:{}",
+                service,
+                self.timeout.as_secs()
+            ))
+        } else {
+            e.into()
+        }
+    }
+}
+
+/// Reads every certificate in the PEM file, since a bundle usually has several.
+fn read_certificates(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    const END: &str = "-----END CERTIFICATE-----";
+    let pem = fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!(
+            "cannot read the CA bundle. path:{}, error:{}",
+            path.display(),
+            e
+        )
+    })?;
+
+    let mut certificates = Vec::new();
+    let mut rest = pem.as_str();
+    while let Some(start) = rest.find("-----BEGIN CERTIFICATE-----") {
+        let Some(end) = rest[start..].find(END).map(|end| start + end + END.len()) else {
+            break;
+        };
+        let certificate = Certificate::from_pem(&rest.as_bytes()[start..end]).map_err(|e| {
+            anyhow::anyhow!(
+                "invalid certificate in the CA bundle. path:{}, error:{}",
+                path.display(),
+                e
+            )
+        })?;
+        certificates.push(certificate);
+        rest = &rest[end..];
+    }
+    if certificates.is_empty() {
+        anyhow::bail!(
+            "No certificate found in the CA bundle. path:{}",
+            path.display()
+        );
+    }
+    Ok(certificates)
+}
+
+#[derive(Debug, Clone)]
+struct SdkHttpClient {
+    client: reqwest::Client,
+    settings: NetworkSettings,
+}
+
+impl HttpClient for SdkHttpClient {
+    fn http_connector(
+        &self,
+        _settings: &HttpConnectorSettings,
+        _components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(self.clone())
+    }
+}
+
+impl HttpConnector for SdkHttpClient {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let client = self.clone();
+        HttpConnectorFuture::new(async move { client.send(request).await })
+    }
+}
+
+impl SdkHttpClient {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ConnectorError> {
+        let method = reqwest::Method::from_bytes(request.method().as_bytes())
+            .map_err(|e| ConnectorError::user(e.into()))?;
+        // the requests of STS and SSO are small enough to be in memory.
+        let body = request
+            .body()
+            .bytes()
+            .ok_or_else(|| ConnectorError::user("streaming bodies are not supported.".into()))?
+            .to_vec();
+        let mut builder = self.client.request(method, request.uri()).body(body);
+        for (name, value) in request.headers().iter() {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder.send().await.map_err(|e| self.connector_error(e))?;
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| self.connector_error(e))?;
+
+        let status = status
+            .try_into()
+            .map_err(|e| ConnectorError::other(Box::new(e), None))?;
+        let mut response = HttpResponse::new(status, SdkBody::from(body));
+        for (name, value) in headers.iter() {
+            if let Ok(value) = value.to_str() {
+                response
+                    .headers_mut()
+                    .append(name.as_str().to_string(), value.to_string());
+            }
+        }
+        Ok(response)
+    }
+
+    fn connector_error(&self, e: reqwest::Error) -> ConnectorError {
+        if e.is_timeout() {
+            ConnectorError::timeout(self.settings.error(e).into())
+        } else if e.is_connect() {
+            ConnectorError::io(e.into())
+        } else {
+            ConnectorError::other(e.into(), None)
+        }
+    }
+}
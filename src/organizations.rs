@@ -0,0 +1,351 @@
+use std::env;
+use std::time::{Duration, SystemTime};
+
+use assume_rolers_schema::credentials::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use aws_smithy_runtime_api::client::identity::Identity;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::debug;
+
+use crate::network::NetworkSettings;
+use crate::partition::Partition;
+
+mod endpoints {
+    /// The standard variable to override the endpoint, as AWS SDKs do.
+    pub const ENDPOINT_URL_VAR: &str = "AWS_ENDPOINT_URL_ORGANIZATIONS";
+    pub const SIGNING_NAME: &str = "organizations";
+    pub const TARGET_PREFIX: &str = "AWSOrganizationsV20161128";
+}
+
+/// ListAccounts throttles heavily, so the calls are retried with exponential backoff.
+const MAX_ATTEMPTS: u32 = 6;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+    /// `ACTIVE`, `SUSPENDED` or `PENDING_CLOSURE`.
+    pub status: Option<String>,
+}
+
+impl Account {
+    pub fn is_active(&self) -> bool {
+        self.status.as_deref().unwrap_or("ACTIVE") == "ACTIVE"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListAccountsResponse {
+    #[serde(default)]
+    accounts: Vec<Account>,
+    next_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(rename = "__type")]
+    error_type: Option<String>,
+    #[serde(rename = "Message", alias = "message")]
+    message: Option<String>,
+}
+
+impl ErrorResponse {
+    /// The type without the namespace, e.g. `TooManyRequestsException`.
+    fn error_type(&self) -> Option<&str> {
+        self.error_type
+            .as_deref()
+            .map(|t| t.rsplit_once('#').map(|(_, t)| t).unwrap_or(t))
+    }
+}
+
+fn is_retryable(status: reqwest::StatusCode, error: Option<&ErrorResponse>) -> bool {
+    status.is_server_error()
+        || matches!(
+            error.and_then(|e| e.error_type()),
+            Some("TooManyRequestsException" | "ThrottlingException")
+        )
+}
+
+/// A minimal client of the AWS Organizations API, which speaks its JSON protocol with SigV4.
+pub struct OrganizationsClient {
+    credentials: Credentials,
+    endpoint: Url,
+    signing_region: &'static str,
+    client: reqwest::Client,
+    network: NetworkSettings,
+    retry_delay: Duration,
+}
+
+impl OrganizationsClient {
+    /// Calls the endpoint of the partition, or `AWS_ENDPOINT_URL_ORGANIZATIONS` if set.
+    pub fn new(
+        credentials: Credentials,
+        partition: Partition,
+        network: NetworkSettings,
+    ) -> anyhow::Result<Self> {
+        let signing_region = partition.global_region();
+        let endpoint = env::var(endpoints::ENDPOINT_URL_VAR)
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "https://organizations.{}.{}",
+                    signing_region,
+                    partition.dns_suffix()
+                )
+            });
+        Ok(OrganizationsClient {
+            credentials,
+            endpoint: endpoint.parse()?,
+            signing_region,
+            client: network.client()?,
+            network,
+            retry_delay: RETRY_DELAY,
+        })
+    }
+
+    /// Overrides the endpoint, e.g. with a local stand-in for testing.
+    pub fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let body = serde_json::to_vec(&body)?;
+        let mut attempt = 1;
+        loop {
+            let (status, text) = self.send(operation, &body).await?;
+            if status.is_success() {
+                return Ok(serde_json::from_str(&text)?);
+            }
+
+            let error = serde_json::from_str::<ErrorResponse>(&text).ok();
+            if attempt < MAX_ATTEMPTS && is_retryable(status, error.as_ref()) {
+                let delay = self.retry_delay * 2u32.pow(attempt - 1);
+                debug!(
+                    "retry the Organizations API. operation:{}, status:{}, attempt:{}, delay:{:?}",
+                    operation, status, attempt, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+            anyhow::bail!(
+                "Organizations API failed. operation:{}, status:{}, type:{}, message:{}",
+                operation,
+                status,
+                error.as_ref().and_then(|e| e.error_type()).unwrap_or("-"),
+                error
+                    .as_ref()
+                    .and_then(|e| e.message.as_deref())
+                    .unwrap_or(&text),
+            );
+        }
+    }
+
+    /// Sends the request signed at the time, since a retry needs a fresh signature.
+    async fn send(
+        &self,
+        operation: &str,
+        body: &[u8],
+    ) -> anyhow::Result<(reqwest::StatusCode, String)> {
+        let target = format!("{}.{}", endpoints::TARGET_PREFIX, operation);
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("x-amz-target", target.as_str()),
+        ];
+
+        let identity = Identity::from(aws_credential_types::Credentials::new(
+            self.credentials.key(),
+            self.credentials.secret(),
+            self.credentials.token().map(|s| s.to_string()),
+            None,
+            "assume-rolers",
+        ));
+        let params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(self.signing_region)
+            .name(endpoints::SIGNING_NAME)
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()?
+            .into();
+        let signable = SignableRequest::new(
+            "POST",
+            self.endpoint.as_str(),
+            headers.iter().copied(),
+            SignableBody::Bytes(body),
+        )?;
+        let (instructions, _) = sign(signable, &params)?.into_parts();
+
+        let mut request = self.client.post(self.endpoint.clone()).body(body.to_vec());
+        for (name, value) in headers.iter().copied().chain(instructions.headers()) {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| self.network.error(e))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| self.network.error(e))?;
+        Ok((status, text))
+    }
+
+    /// Lists all the accounts in the organization, following the pages.
+    pub async fn list_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        let mut accounts = Vec::new();
+        let mut next_token = None;
+        loop {
+            let body = match next_token.take() {
+                Some(token) => json!({ "NextToken": token }),
+                None => json!({}),
+            };
+            let response = self
+                .call::<ListAccountsResponse>("ListAccounts", body)
+                .await?;
+            accounts.extend(response.accounts);
+            match response.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => break,
+            }
+        }
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A local stand-in for the Organizations endpoint, which answers the requests in order
+    /// and records them.
+    async fn stand_in(responses: Vec<(u16, &'static str)>) -> (Url, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                // the request is small, and complete once the body of content-length arrives.
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|l| {
+                                l.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            recorded.lock().unwrap().push(text);
+                            break;
+                        }
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/x-amz-json-1.1\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn client(endpoint: Url) -> OrganizationsClient {
+        let credentials = Credentials {
+            key: "AKIDEXAMPLE".to_string(),
+            secret: "secret".to_string(),
+            token: None,
+            expires_at: None,
+        };
+        let mut client =
+            OrganizationsClient::new(credentials, Partition::Aws, NetworkSettings::default())
+                .unwrap()
+                .with_endpoint(endpoint);
+        client.retry_delay = Duration::from_millis(1);
+        client
+    }
+
+    const THROTTLED: &str = r#"{"__type":"com.amazonaws.organizations#TooManyRequestsException","Message":"slow down"}"#;
+
+    #[tokio::test]
+    async fn list_accounts_follows_pages_and_retries_throttling() {
+        let (url, requests) = stand_in(vec![
+            (400, THROTTLED),
+            (
+                200,
+                r#"{"Accounts":[{"Id":"111111111111","Name":"Prod","Status":"ACTIVE"}],"NextToken":"p2"}"#,
+            ),
+            (500, "{}"),
+            (
+                200,
+                r#"{"Accounts":[{"Id":"222222222222","Name":"Old","Status":"SUSPENDED"}]}"#,
+            ),
+        ])
+        .await;
+
+        let accounts = client(url).list_accounts().await.unwrap();
+        let ids = accounts.iter().map(|a| a.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["111111111111", "222222222222"]);
+        assert!(accounts[0].is_active());
+        assert!(!accounts[1].is_active());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        for request in requests.iter() {
+            let request = request.to_lowercase();
+            assert!(request.contains("x-amz-target: awsorganizationsv20161128.listaccounts"));
+            assert!(request.contains("authorization: aws4-hmac-sha256 credential=akidexample/"));
+        }
+        assert!(requests[3].ends_with(r#"{"NextToken":"p2"}"#));
+    }
+
+    #[tokio::test]
+    async fn list_accounts_gives_up_on_other_errors() {
+        let (url, requests) = stand_in(vec![(
+            400,
+            r#"{"__type":"AccessDeniedException","Message":"not the management account"}"#,
+        )])
+        .await;
+
+        let error = client(url).list_accounts().await.unwrap_err().to_string();
+        assert!(error.contains("type:AccessDeniedException"), "{}", error);
+        assert!(error.contains("not the management account"), "{}", error);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn list_accounts_gives_up_after_max_attempts() {
+        let (url, requests) = stand_in(vec![(400, THROTTLED); MAX_ATTEMPTS as usize]).await;
+
+        let error = client(url).list_accounts().await.unwrap_err().to_string();
+        assert!(error.contains("type:TooManyRequestsException"), "{}", error);
+        assert_eq!(requests.lock().unwrap().len(), MAX_ATTEMPTS as usize);
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/// Formats for scripting. Plain text is the default.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Csv,
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 3] = ["json", "yaml", "csv"];
+
+    pub fn write<T: Serialize, W: Write>(&self, mut writer: W, rows: &[T]) -> anyhow::Result<()> {
+        match self {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, rows)?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Yaml => serde_yaml::to_writer(&mut writer, rows)?,
+            OutputFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(anyhow::anyhow!(
+                "unknown output format. format:{}, available formats: {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Writes the rows as a table aligned by the widest cell of each column.
+pub fn write_table<W: Write>(
+    mut writer: W,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> anyhow::Result<()> {
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .chain([h.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let header = headers.iter().map(|h| h.to_uppercase()).collect::<Vec<_>>();
+    for row in [header].iter().chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, w)| format!("{:<w$}", cell, w = w))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
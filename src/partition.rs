@@ -0,0 +1,98 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// An AWS partition, which has its own hosts, regions and ARNs.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Partition {
+    #[default]
+    Aws,
+    AwsCn,
+    AwsUsGov,
+}
+
+impl Partition {
+    pub const NAMES: [&'static str; 3] = ["aws", "aws-cn", "aws-us-gov"];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Partition::Aws => "aws",
+            Partition::AwsCn => "aws-cn",
+            Partition::AwsUsGov => "aws-us-gov",
+        }
+    }
+
+    /// Returns the partition of the region, e.g. `aws-cn` for `cn-north-1`.
+    pub fn from_region(region: &str) -> Self {
+        if region.starts_with("cn-") {
+            Partition::AwsCn
+        } else if region.starts_with("us-gov-") {
+            Partition::AwsUsGov
+        } else {
+            Partition::Aws
+        }
+    }
+
+    /// The region of the clients when nothing specifies one.
+    pub fn default_region(&self) -> &'static str {
+        match self {
+            Partition::Aws => "us-east-1",
+            Partition::AwsCn => "cn-north-1",
+            Partition::AwsUsGov => "us-gov-west-1",
+        }
+    }
+
+    /// The region which hosts the global services such as Organizations.
+    pub fn global_region(&self) -> &'static str {
+        match self {
+            Partition::Aws => "us-east-1",
+            Partition::AwsCn => "cn-northwest-1",
+            Partition::AwsUsGov => "us-gov-west-1",
+        }
+    }
+
+    pub fn dns_suffix(&self) -> &'static str {
+        match self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+        }
+    }
+
+    pub fn federation_endpoint(&self) -> &'static str {
+        match self {
+            Partition::Aws => "https://signin.aws.amazon.com/federation",
+            Partition::AwsCn => "https://signin.amazonaws.cn/federation",
+            Partition::AwsUsGov => "https://signin.amazonaws-us-gov.com/federation",
+        }
+    }
+
+    pub fn console_url(&self) -> &'static str {
+        match self {
+            Partition::Aws => "https://console.aws.amazon.com/",
+            Partition::AwsCn => "https://console.amazonaws.cn/",
+            Partition::AwsUsGov => "https://console.amazonaws-us-gov.com/",
+        }
+    }
+}
+
+impl FromStr for Partition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aws" => Ok(Partition::Aws),
+            "aws-cn" => Ok(Partition::AwsCn),
+            "aws-us-gov" => Ok(Partition::AwsUsGov),
+            _ => Err(anyhow::anyhow!(
+                "unknown partition. partition:{}, expected one of: {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
@@ -1,19 +1,84 @@
 use std::collections::BTreeMap;
-
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::partition::Partition;
+use crate::profile::arn::RoleArn;
+
+pub mod arn;
+pub mod cli;
+pub mod edit;
+pub mod files;
+pub mod filter;
+pub mod generate;
+pub mod graph;
+pub mod ini;
+pub mod lint;
 pub mod load;
+pub mod resolve;
 pub mod select;
+pub mod wizard;
+
+/// How the profile provides credentials.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfileKind {
+    Role,
+    StaticKeys,
+    Sso,
+    CredentialProcess,
+    Other,
+}
+
+impl Display for ProfileKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ProfileKind::Role => "role",
+            ProfileKind::StaticKeys => "static-keys",
+            ProfileKind::Sso => "sso",
+            ProfileKind::CredentialProcess => "credential-process",
+            ProfileKind::Other => "other",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The settings of the endpoints of the profile, which apply to STS.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointSettings {
+    pub endpoint_url: Option<String>,
+    /// The PEM file of the CA certificates to trust, e.g. of a proxy inspecting TLS.
+    pub ca_bundle: Option<String>,
+    /// `regional` or `legacy`.
+    pub sts_regional_endpoints: Option<String>,
+    /// `true` or `false`, which is checked when the profile is assumed so that
+    /// an invalid value doesn't break the other profiles.
+    pub use_fips_endpoint: Option<String>,
+    pub use_dualstack_endpoint: Option<String>,
+    pub ignore_configured_endpoint_urls: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Profile {
     pub name: String,
+    pub kind: ProfileKind,
     pub source_profile_name: Option<String>,
     pub region_name: Option<String>,
     pub role_arn: Option<String>,
+    pub credential_source: Option<String>,
     pub role_session_name: Option<String>,
     pub external_id: Option<String>,
     pub duration_seconds: Option<u32>,
     pub scope_down_policy: Option<String>,
     pub mfa_serial: Option<String>,
+    pub endpoint: EndpointSettings,
+    pub tags: BTreeMap<String, String>,
+    /// The file which defines the profile with the highest precedence.
+    pub source_file: Option<PathBuf>,
+    /// Built at runtime rather than read from the AWS config, so the SDK can't resolve it by name.
+    pub synthesized: bool,
 }
 
 impl Profile {
@@ -37,6 +102,23 @@ impl Profile {
         self.role_arn.as_deref()
     }
 
+    pub fn role(&self) -> Option<RoleArn> {
+        self.role_arn().and_then(|arn| arn.parse().ok())
+    }
+
+    /// The partition of the role ARN, or of the region for the other profiles.
+    pub fn partition(&self) -> Partition {
+        match (self.role(), self.region_name()) {
+            (Some(arn), _) => arn.partition,
+            (None, Some(region)) => Partition::from_region(region),
+            (None, None) => Partition::default(),
+        }
+    }
+
+    pub fn credential_source(&self) -> Option<&str> {
+        self.credential_source.as_deref()
+    }
+
     pub fn role_session_name(&self) -> Option<&str> {
         self.role_session_name.as_deref()
     }
@@ -56,6 +138,14 @@ impl Profile {
     pub fn mfa_serial(&self) -> Option<&str> {
         self.mfa_serial.as_deref()
     }
+
+    pub fn source_file(&self) -> Option<&Path> {
+        self.source_file.as_deref()
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(|s| s.as_str())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,4 +165,20 @@ impl ProfileSet {
     pub fn profiles(&self) -> impl Iterator<Item = &Profile> {
         self.profiles.values()
     }
+
+    /// Returns the profile and its source profiles, stopping at a missing profile or a cycle.
+    pub fn source_chain(&self, profile_name: &str) -> Vec<&Profile> {
+        let mut chain = Vec::<&Profile>::new();
+        let mut next = self.get_profile(profile_name);
+        while let Some(profile) = next {
+            if chain.iter().any(|p| p.name == profile.name) {
+                break;
+            }
+            chain.push(profile);
+            next = profile
+                .source_profile_name()
+                .and_then(|name| self.get_profile(name));
+        }
+        chain
+    }
 }
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::partition::Partition;
+
+/// Parts of an IAM role ARN, e.g. `arn:aws:iam::123456789012:role/path/RoleName`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoleArn {
+    pub partition: Partition,
+    pub account_id: String,
+    pub path: String,
+    pub role_name: String,
+}
+
+impl RoleArn {
+    /// Builds the ARN of the role without a path.
+    pub fn new(partition: Partition, account_id: &str, role_name: &str) -> anyhow::Result<Self> {
+        format!("arn:{}:iam::{}:role/{}", partition, account_id, role_name).parse()
+    }
+}
+
+impl FromStr for RoleArn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow::anyhow!("invalid role ARN. arn:{}", s);
+        let parts = s.splitn(6, ':').collect::<Vec<_>>();
+        let [arn, partition, service, region, account_id, resource] = parts[..] else {
+            return Err(invalid());
+        };
+        if arn != "arn" || service != "iam" || !region.is_empty() {
+            return Err(invalid());
+        }
+        let partition = partition.parse::<Partition>().map_err(|_| invalid())?;
+        if !is_account_id(account_id) {
+            return Err(invalid());
+        }
+
+        let resource = resource.strip_prefix("role/").ok_or_else(invalid)?;
+        let (path, role_name) = match resource.rsplit_once('/') {
+            Some((path, role_name)) => (format!("/{}/", path), role_name),
+            None => ("/".to_string(), resource),
+        };
+        if role_name.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(RoleArn {
+            partition,
+            account_id: account_id.to_string(),
+            path,
+            role_name: role_name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for RoleArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arn:{}:iam::{}:role{}{}",
+            self.partition, self.account_id, self.path, self.role_name
+        )
+    }
+}
+
+pub fn is_account_id(s: &str) -> bool {
+    s.len() == 12 && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_partition() {
+        let cases = [
+            ("arn:aws:iam::123456789012:role/Admin", Partition::Aws),
+            ("arn:aws-cn:iam::123456789012:role/Admin", Partition::AwsCn),
+            (
+                "arn:aws-us-gov:iam::123456789012:role/Admin",
+                Partition::AwsUsGov,
+            ),
+        ];
+        for (arn, partition) in cases {
+            let role = arn.parse::<RoleArn>().unwrap();
+            assert_eq!(role.partition, partition, "{}", arn);
+            assert_eq!(role.account_id, "123456789012");
+            assert_eq!(role.role_name, "Admin");
+            assert_eq!(role.to_string(), arn);
+        }
+    }
+
+    #[test]
+    fn parses_the_path() {
+        let role = "arn:aws:iam::123456789012:role/ops/team/Admin"
+            .parse::<RoleArn>()
+            .unwrap();
+        assert_eq!(role.path, "/ops/team/");
+        assert_eq!(role.role_name, "Admin");
+        assert_eq!(
+            role.to_string(),
+            "arn:aws:iam::123456789012:role/ops/team/Admin"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_arns() {
+        for arn in [
+            "arn:aws-xx:iam::123456789012:role/Admin",
+            "arn:aws:iam::12345:role/Admin",
+            "arn:aws:iam:us-east-1:123456789012:role/Admin",
+            "arn:aws:sts::123456789012:role/Admin",
+            "arn:aws:iam::123456789012:user/Admin",
+            "arn:aws:iam::123456789012:role/",
+            "Admin",
+        ] {
+            assert!(arn.parse::<RoleArn>().is_err(), "{}", arn);
+        }
+    }
+
+    #[test]
+    fn new_builds_the_arn_in_the_partition() {
+        let role = RoleArn::new(Partition::AwsCn, "123456789012", "Admin").unwrap();
+        assert_eq!(role.to_string(), "arn:aws-cn:iam::123456789012:role/Admin");
+        assert!(RoleArn::new(Partition::Aws, "123", "Admin").is_err());
+    }
+}
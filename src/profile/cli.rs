@@ -0,0 +1,628 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::ArgAction;
+use serde::Serialize;
+
+use crate::assume_role::aws_sdk::AwsSdkAssumeRole;
+use crate::assume_role::cache::SessionCache;
+use crate::config::Config;
+use crate::dirs;
+use crate::mfa::MfaReader;
+use crate::network::NetworkSettings;
+use crate::output::{write_table, OutputFormat};
+use crate::profile::arn::RoleArn;
+use crate::profile::edit::{Assignment, ConfigEditor};
+use crate::profile::files::{FileKind, ProfileFiles};
+use crate::profile::filter::ProfileFilter;
+use crate::profile::generate::ConfigUpdate;
+use crate::profile::graph::{GraphFormat, ProfileGraph};
+use crate::profile::lint::{Linter, Severity, KNOWN_KEYS};
+use crate::profile::load::aws_sdk::AwsSdkProfileLoader;
+use crate::profile::load::config::ConfigProfileLoader;
+use crate::profile::load::LoadProfiles;
+use crate::profile::wizard::ProfileWizard;
+use crate::profile::{Profile, ProfileKind, ProfileSet};
+
+/// Asks the question on the terminal. Anything but yes is no.
+pub fn confirm(question: &str) -> anyhow::Result<bool> {
+    eprint!("{} [y/N]: ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[derive(Debug)]
+pub struct ListProfiles {
+    group: Option<String>,
+    filter: ProfileFilter,
+    long: bool,
+    output: Option<OutputFormat>,
+    all: bool,
+}
+
+impl ListProfiles {
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let loader = ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone());
+        let profiles = loader.load_profiles().await?;
+        let filter = &self.filter;
+        let matches = |name: &str| {
+            profiles
+                .get_profile(name)
+                .map(|p| filter.matches(p))
+                .unwrap_or_else(|| filter.is_empty())
+        };
+
+        let names = if let Some(group) = self.group.as_ref() {
+            config.group(group).unwrap_or_default()
+        } else {
+            profiles
+                .profiles()
+                .filter(|p| self.all || p.has_role_arn())
+                .map(|p| p.name().to_string())
+                .collect()
+        };
+        let names = names
+            .into_iter()
+            .filter(|name| matches(name))
+            .collect::<Vec<_>>();
+
+        if self.long || self.output.is_some() {
+            let cache = config.cache.enabled.then(SessionCache::default);
+            let rows = names
+                .iter()
+                .filter_map(|name| profiles.get_profile(name))
+                .map(|p| ProfileRow::new(p, &profiles, cache.as_ref()))
+                .collect::<Vec<_>>();
+            let stdout = std::io::stdout().lock();
+            return match self.output {
+                Some(format) => format.write(stdout, &rows),
+                None => write_table(
+                    stdout,
+                    &ProfileRow::HEADERS,
+                    &rows.iter().map(|r| r.cells()).collect::<Vec<_>>(),
+                ),
+            };
+        }
+
+        // aliases are listed for the completion, unless the group is specified.
+        let aliases = if self.group.is_some() {
+            Vec::default()
+        } else {
+            config
+                .alias_pairs(&names)
+                .into_iter()
+                .map(|(alias, _)| alias)
+                .collect()
+        };
+        for p in names.into_iter().chain(aliases) {
+            println!("{}", p);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for ListProfiles {
+    fn from(m: &clap::ArgMatches) -> Self {
+        ListProfiles {
+            group: m.get_one::<String>("group").map(|s| s.to_string()),
+            filter: ProfileFilter::from(m),
+            long: m.get_flag("long"),
+            output: m.get_one::<OutputFormat>("output").copied(),
+            all: m.get_flag("all"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ProfileRow {
+    name: String,
+    kind: ProfileKind,
+    account_id: Option<String>,
+    role_name: Option<String>,
+    region: Option<String>,
+    source_profile: Option<String>,
+    mfa_serial: Option<String>,
+    duration_seconds: Option<u32>,
+    cached_remaining_seconds: Option<i64>,
+    source_file: Option<String>,
+}
+
+impl ProfileRow {
+    const HEADERS: [&'static str; 9] = [
+        "name", "account", "role", "region", "source", "mfa", "duration", "cached", "file",
+    ];
+
+    fn new(profile: &Profile, profiles: &ProfileSet, cache: Option<&SessionCache>) -> Self {
+        let role = profile.role();
+        let region = profiles
+            .source_chain(profile.name())
+            .iter()
+            .find_map(|p| p.region_name())
+            .map(|s| s.to_string());
+        let cached = cache
+            .and_then(|cache| cache.get(profile))
+            .and_then(|session| session.remaining());
+
+        ProfileRow {
+            name: profile.name().to_string(),
+            kind: profile.kind,
+            account_id: role.as_ref().map(|r| r.account_id.to_string()),
+            role_name: role.as_ref().map(|r| r.role_name.to_string()),
+            region,
+            source_profile: profile.source_profile_name().map(|s| s.to_string()),
+            mfa_serial: profile.mfa_serial().map(|s| s.to_string()),
+            duration_seconds: profile.duration_seconds(),
+            cached_remaining_seconds: cached.map(|d| d.num_seconds()),
+            source_file: profile.source_file().map(|p| p.display().to_string()),
+        }
+    }
+
+    fn cells(&self) -> Vec<String> {
+        fn cell<T: ToString>(value: Option<T>) -> String {
+            value
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        }
+
+        vec![
+            self.name.to_string(),
+            cell(self.account_id.as_ref()),
+            // profiles without a role show how they provide credentials instead.
+            cell(
+                self.role_name.clone().or_else(|| {
+                    (self.kind != ProfileKind::Role).then(|| format!("({})", self.kind))
+                }),
+            ),
+            cell(self.region.as_ref()),
+            cell(self.source_profile.as_ref()),
+            if self.mfa_serial.is_some() {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+            cell(self.duration_seconds.map(|n| format!("{}s", n))),
+            cell(
+                self.cached_remaining_seconds
+                    .map(|n| format!("{}m", n / 60)),
+            ),
+            cell(self.source_file.as_ref()),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct Graph {
+    format: GraphFormat,
+    highlight: Option<String>,
+}
+
+impl Graph {
+    pub fn command() -> clap::Command {
+        clap::Command::new("graph")
+            .about("Show the relationships between the profiles by source_profile.")
+            .arg(
+                clap::Arg::new("format")
+                    .short('f')
+                    .long("format")
+                    .default_value("tree")
+                    .value_parser(
+                        PossibleValuesParser::new(GraphFormat::NAMES)
+                            .try_map(|s| s.parse::<GraphFormat>()),
+                    )
+                    .help("Specify the output format."),
+            )
+            .arg(
+                clap::Arg::new("highlight")
+                    .long("highlight")
+                    .value_hint(clap::ValueHint::Other)
+                    .help("Highlight the path used to assume the profile."),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let loader = ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone());
+        let profiles = loader.load_profiles().await?;
+        let mut profile_graph = ProfileGraph::from(&profiles);
+        if let Some(name) = self.highlight.as_ref() {
+            let name = config.resolve_alias(name);
+            if profiles.get_profile(name).is_none() {
+                anyhow::bail!("No profile found. profile_name:{}", name);
+            }
+            profile_graph = profile_graph.with_highlight(&profiles, name);
+        }
+
+        print!("{}", profile_graph.render(self.format));
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for Graph {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Graph {
+            format: *m
+                .get_one::<GraphFormat>("format")
+                .expect("default value exists"),
+            highlight: m.get_one::<String>("highlight").map(|s| s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Lint {
+    strict: bool,
+}
+
+impl Lint {
+    pub fn command() -> clap::Command {
+        clap::Command::new("lint")
+            .about("Check the profiles in the AWS config and credentials files.")
+            .arg(
+                clap::Arg::new("strict")
+                    .long("strict")
+                    .action(ArgAction::SetTrue)
+                    .help("Fail on warnings as well as errors."),
+            )
+    }
+
+    pub fn run(self, files: ProfileFiles) -> anyhow::Result<()> {
+        let scanned = files.scan()?;
+        let problems = Linter::new(&scanned).lint();
+        for problem in problems.iter() {
+            println!("{}", problem);
+        }
+
+        let errors = problems
+            .iter()
+            .filter(|p| p.severity == Severity::Error)
+            .count();
+        let warnings = problems.len() - errors;
+        if errors > 0 || (self.strict && warnings > 0) {
+            anyhow::bail!("{} error(s) and {} warning(s) found.", errors, warnings);
+        }
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for Lint {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Lint {
+            strict: m.get_flag("strict"),
+        }
+    }
+}
+
+/// The `profile` command, whose `new` subcommand is [`NewProfile`] and the others are [`EditProfile`].
+pub fn profile_command() -> clap::Command {
+    clap::Command::new("profile")
+        .about("Edit the profiles, keeping the comments and formatting of the file.")
+        .subcommand_required(true)
+        .subcommand(
+            clap::Command::new("add")
+                .about("Add a profile.")
+                .arg(
+                    clap::Arg::new("name")
+                        .required(true)
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the name of the profile."),
+                )
+                .arg(
+                    clap::Arg::new("properties")
+                        .num_args(0..)
+                        .value_parser(|s: &str| s.parse::<Assignment>())
+                        .value_name("KEY=VALUE")
+                        .help("Specify the properties of the profile."),
+                )
+                .arg(
+                    clap::Arg::new("role-arn")
+                        .long("role-arn")
+                        .value_parser(|s: &str| s.parse::<RoleArn>())
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the role_arn."),
+                )
+                .arg(
+                    clap::Arg::new("source-profile")
+                        .long("source-profile")
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the source_profile."),
+                )
+                .arg(
+                    clap::Arg::new("mfa-serial")
+                        .long("mfa-serial")
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the mfa_serial."),
+                )
+                .arg(
+                    clap::Arg::new("region")
+                        .long("region")
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the region."),
+                )
+                .args(edit_args()),
+        )
+        .subcommand(NewProfile::command())
+        .subcommand(
+            clap::Command::new("set")
+                .about("Add or update the properties of a profile.")
+                .arg(
+                    clap::Arg::new("name")
+                        .required(true)
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the name of the profile."),
+                )
+                .arg(
+                    clap::Arg::new("properties")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(|s: &str| s.parse::<Assignment>())
+                        .value_name("KEY=VALUE")
+                        .help("Specify the properties to set."),
+                )
+                .args(edit_args()),
+        )
+        .subcommand(
+            clap::Command::new("rm")
+                .about("Remove the properties of a profile, or the profile itself without keys.")
+                .arg(
+                    clap::Arg::new("name")
+                        .required(true)
+                        .value_hint(clap::ValueHint::Other)
+                        .help("Specify the name of the profile."),
+                )
+                .arg(
+                    clap::Arg::new("keys")
+                        .num_args(0..)
+                        .value_name("KEY")
+                        .help("Specify the keys to remove."),
+                )
+                .args(edit_args()),
+        )
+}
+
+/// Arguments of the commands which edit a profile in place.
+fn edit_args() -> [clap::Arg; 3] {
+    [
+        clap::Arg::new("file")
+            .long("file")
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_hint(clap::ValueHint::FilePath)
+            .help("Specify the file to edit. Defaults to the AWS config file, or the credentials file with --credentials."),
+        clap::Arg::new("credentials")
+            .long("credentials")
+            .action(ArgAction::SetTrue)
+            .help("Edit the profile in the credentials file instead."),
+        clap::Arg::new("dry-run")
+            .long("dry-run")
+            .action(ArgAction::SetTrue)
+            .help("Show the changes without updating the file."),
+    ]
+}
+
+#[derive(Debug)]
+pub enum ProfileAction {
+    Add(Vec<Assignment>),
+    Set(Vec<Assignment>),
+    Rm(Vec<String>),
+}
+
+#[derive(Debug)]
+pub struct EditProfile {
+    name: String,
+    action: ProfileAction,
+    kind: FileKind,
+    file: Option<PathBuf>,
+    dry_run: bool,
+}
+
+impl EditProfile {
+    /// `name` is the name of the subcommand, `add`, `set` or `rm`.
+    pub fn from(name: &str, m: &clap::ArgMatches) -> Self {
+        let properties = |m: &clap::ArgMatches| {
+            m.get_many::<Assignment>("properties")
+                .map(|v| v.cloned().collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let action = match name {
+            "add" => {
+                let flags = [
+                    (
+                        "role_arn",
+                        m.get_one::<RoleArn>("role-arn").map(|a| a.to_string()),
+                    ),
+                    (
+                        "source_profile",
+                        m.get_one::<String>("source-profile").cloned(),
+                    ),
+                    ("mfa_serial", m.get_one::<String>("mfa-serial").cloned()),
+                    ("region", m.get_one::<String>("region").cloned()),
+                ];
+                let assignments = flags
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        value.map(|value| Assignment {
+                            key: key.to_string(),
+                            value,
+                        })
+                    })
+                    .chain(properties(m))
+                    .collect();
+                ProfileAction::Add(assignments)
+            }
+            "set" => ProfileAction::Set(properties(m)),
+            _ => ProfileAction::Rm(
+                m.get_many::<String>("keys")
+                    .map(|v| v.cloned().collect())
+                    .unwrap_or_default(),
+            ),
+        };
+        EditProfile {
+            name: m.get_one::<String>("name").expect("required").to_string(),
+            action,
+            kind: if m.get_flag("credentials") {
+                FileKind::Credentials
+            } else {
+                FileKind::Config
+            },
+            file: m.get_one::<PathBuf>("file").cloned(),
+            dry_run: m.get_flag("dry-run"),
+        }
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let path = self
+            .file
+            .or_else(|| match self.kind {
+                FileKind::Config => dirs::aws_config_file(),
+                FileKind::Credentials => dirs::aws_credentials_file(),
+            })
+            .ok_or_else(|| match self.kind {
+                FileKind::Config => anyhow::anyhow!("cannot find the AWS config file."),
+                FileKind::Credentials => anyhow::anyhow!("cannot find the AWS credentials file."),
+            })?;
+        let warn_unknown = |assignments: &[Assignment]| {
+            for a in assignments
+                .iter()
+                .filter(|a| !KNOWN_KEYS.contains(&a.key.as_str()))
+            {
+                eprintln!("unknown key. profile:{}, key:{}", self.name, a.key);
+            }
+        };
+
+        let update = ConfigUpdate::with(&path, |content| {
+            let mut editor = ConfigEditor::new(self.kind, content);
+            match &self.action {
+                ProfileAction::Add(assignments) => {
+                    warn_unknown(assignments);
+                    editor.add_profile(&self.name)?;
+                    for a in assignments {
+                        editor.set(&self.name, &a.key, &a.value)?;
+                    }
+                }
+                ProfileAction::Set(assignments) => {
+                    warn_unknown(assignments);
+                    for a in assignments {
+                        editor.set(&self.name, &a.key, &a.value)?;
+                    }
+                }
+                ProfileAction::Rm(keys) if keys.is_empty() => editor.remove_profile(&self.name)?,
+                ProfileAction::Rm(keys) => {
+                    for key in keys {
+                        if !editor.unset(&self.name, key)? {
+                            eprintln!("no such key. profile:{}, key:{}", self.name, key);
+                        }
+                    }
+                }
+            }
+            Ok(editor.content())
+        })?;
+
+        if update.is_empty() {
+            eprintln!("no changes. path:{}", update.path.display());
+            return Ok(());
+        }
+        if self.dry_run {
+            print!("{}", update.diff());
+            return Ok(());
+        }
+        update.apply()?;
+        eprintln!("updated {}.", update.path.display());
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct NewProfile {
+    name: Option<String>,
+    file: Option<PathBuf>,
+    no_test: bool,
+}
+
+impl NewProfile {
+    fn command() -> clap::Command {
+        clap::Command::new("new")
+            .about("Create a role profile interactively, and test it before saving.")
+            .arg(
+                clap::Arg::new("name")
+                    .value_hint(clap::ValueHint::Other)
+                    .help("Specify the name of the profile."),
+            )
+            .arg(
+                clap::Arg::new("file")
+                    .long("file")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .value_hint(clap::ValueHint::FilePath)
+                    .help("Specify the config file to update. Defaults to the AWS config file."),
+            )
+            .arg(
+                clap::Arg::new("no-test")
+                    .long("no-test")
+                    .action(ArgAction::SetTrue)
+                    .help("Save the profile without assuming the role first."),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let profiles = ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone())
+            .load_profiles()
+            .await?;
+        if let Some(name) = self.name.as_deref() {
+            if profiles.get_profile(name).is_some() {
+                anyhow::bail!("The profile already exists. profile_name:{}", name);
+            }
+        }
+        let new = ProfileWizard::new(&profiles).run(self.name)?;
+
+        if !self.no_test {
+            eprintln!("assuming {}...", new.role_arn);
+            let result = AwsSdkAssumeRole::from(&files)
+                .with_network(NetworkSettings::from(&config.network))
+                .profile_credentials(&new.profile(), MfaReader::new(None, &config.mfa))
+                .await;
+            match result {
+                Ok(_) => eprintln!("assumed the role successfully."),
+                Err(e) => {
+                    eprintln!("cannot assume the role. error:{}", e);
+                    if !confirm("save the profile anyway?")? {
+                        eprintln!("canceled.");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        let path = self
+            .file
+            .or_else(dirs::aws_config_file)
+            .ok_or_else(|| anyhow::anyhow!("cannot find the AWS config file."))?;
+        let update = ConfigUpdate::with(&path, |content| {
+            let mut editor = ConfigEditor::new(FileKind::Config, content);
+            editor.add_profile(&new.name)?;
+            for a in new.properties() {
+                editor.set(&new.name, &a.key, &a.value)?;
+            }
+            Ok(editor.content())
+        })?;
+        print!("{}", update.diff());
+        if !confirm(&format!("write the profile to {}?", update.path.display()))? {
+            eprintln!("canceled.");
+            return Ok(());
+        }
+        update.apply()?;
+        eprintln!("updated {}.", update.path.display());
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for NewProfile {
+    fn from(m: &clap::ArgMatches) -> Self {
+        NewProfile {
+            name: m.get_one::<String>("name").map(|s| s.to_string()),
+            file: m.get_one::<PathBuf>("file").cloned(),
+            no_test: m.get_flag("no-test"),
+        }
+    }
+}
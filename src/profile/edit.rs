@@ -0,0 +1,398 @@
+use std::str::FromStr;
+
+use crate::profile::files::FileKind;
+use crate::profile::ini::{section_header, strip_comment};
+
+/// A `key=value` argument.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Assignment {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for Assignment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() && !value.contains('\n') => {
+                Ok(Assignment {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                })
+            }
+            _ => Err(anyhow::anyhow!("expected key=value. input:{}", s)),
+        }
+    }
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';')
+}
+
+fn is_indented(line: &str) -> bool {
+    line.starts_with(char::is_whitespace) && !line.trim().is_empty()
+}
+
+/// A line of the file with its terminator, which is empty for the last line without a newline.
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,
+    ending: &'static str,
+}
+
+/// Edits the profiles in an AWS config or credentials file, keeping the other lines as they are,
+/// including comments, ordering, formatting and line endings.
+#[derive(Debug, Clone)]
+pub struct ConfigEditor {
+    kind: FileKind,
+    lines: Vec<Line>,
+    /// The terminator of the added lines, i.e. that of the first line.
+    newline: &'static str,
+    /// Whether the file ends with a newline, which is kept after editing.
+    trailing_newline: bool,
+}
+
+impl ConfigEditor {
+    pub fn new(kind: FileKind, content: &str) -> Self {
+        let lines = content
+            .split_inclusive('\n')
+            .map(|line| {
+                if let Some(text) = line.strip_suffix("\r\n") {
+                    Line {
+                        text: text.to_string(),
+                        ending: "\r\n",
+                    }
+                } else if let Some(text) = line.strip_suffix('\n') {
+                    Line {
+                        text: text.to_string(),
+                        ending: "\n",
+                    }
+                } else {
+                    Line {
+                        text: line.to_string(),
+                        ending: "",
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let newline = lines
+            .first()
+            .map(|l| l.ending)
+            .filter(|ending| !ending.is_empty())
+            .unwrap_or("\n");
+        ConfigEditor {
+            kind,
+            lines,
+            newline,
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+        }
+    }
+
+    pub fn content(&self) -> String {
+        let mut content = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            content.push_str(&line.text);
+            if i + 1 < self.lines.len() || self.trailing_newline {
+                content.push_str(if line.ending.is_empty() {
+                    self.newline
+                } else {
+                    line.ending
+                });
+            }
+        }
+        content
+    }
+
+    fn line(&self, text: String) -> Line {
+        Line {
+            text,
+            ending: self.newline,
+        }
+    }
+
+    fn header(&self, profile_name: &str) -> String {
+        match self.kind {
+            FileKind::Config if profile_name != "default" => format!("[profile {}]", profile_name),
+            _ => format!("[{}]", profile_name),
+        }
+    }
+
+    /// Returns the range of the lines of the section, from the header to the next header.
+    /// The last one wins if the section is duplicated, as the SDKs do.
+    fn section(&self, profile_name: &str) -> Option<(usize, usize)> {
+        let headers = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.text.trim().starts_with('['))
+            .filter_map(|(i, line)| section_header(&line.text).map(|name| (i, name)))
+            .collect::<Vec<_>>();
+        let position = headers
+            .iter()
+            .rposition(|(_, name)| self.kind.profile_name(name) == Some(profile_name))?;
+        let start = headers[position].0;
+        let end = headers
+            .get(position + 1)
+            .map(|(i, _)| *i)
+            .unwrap_or(self.lines.len());
+        Some((start, end))
+    }
+
+    /// Returns the range of the lines of the property, including the indented lines under it.
+    fn property(&self, (start, end): (usize, usize), key: &str) -> Option<(usize, usize)> {
+        let line = (start + 1..end).rev().find(|&i| {
+            let line = &self.lines[i].text;
+            !is_indented(line)
+                && !is_blank_or_comment(line)
+                && line.split_once('=').map(|(k, _)| k.trim()) == Some(key)
+        })?;
+        let last = (line + 1..end)
+            .take_while(|&i| is_indented(&self.lines[i].text))
+            .last()
+            .unwrap_or(line);
+        Some((line, last + 1))
+    }
+
+    pub fn has_profile(&self, profile_name: &str) -> bool {
+        self.section(profile_name).is_some()
+    }
+
+    /// Appends an empty section for the profile.
+    pub fn add_profile(&mut self, profile_name: &str) -> anyhow::Result<()> {
+        if self.has_profile(profile_name) {
+            anyhow::bail!("The profile already exists. profile_name:{}", profile_name);
+        }
+        if self.lines.last().is_some_and(|l| !l.text.trim().is_empty()) {
+            self.lines.push(self.line(String::new()));
+        }
+        self.lines.push(self.line(self.header(profile_name)));
+        Ok(())
+    }
+
+    /// Updates the value in place, keeping an inline comment, or adds the key at the end of the section.
+    pub fn set(&mut self, profile_name: &str, key: &str, value: &str) -> anyhow::Result<()> {
+        let section = self
+            .section(profile_name)
+            .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", profile_name))?;
+
+        if let Some((line, end)) = self.property(section, key) {
+            let (lhs, rhs) = self.lines[line]
+                .text
+                .split_once('=')
+                .expect("property has '='");
+            let spacing = &rhs[..rhs.len() - rhs.trim_start().len()];
+            // e.g. `s3 =` with nested values has no space after `=`.
+            let spacing = if spacing.is_empty() && lhs.ends_with(' ') {
+                " "
+            } else {
+                spacing
+            };
+            let comment = &rhs[strip_comment(rhs).trim_end().len()..];
+            let updated = Line {
+                text: format!("{}={}{}{}", lhs, spacing, value, comment),
+                ending: self.lines[line].ending,
+            };
+            self.lines.splice(line..end, [updated]);
+        } else {
+            let (start, end) = section;
+            let last = (start + 1..end)
+                .rev()
+                .find(|&i| !is_blank_or_comment(&self.lines[i].text))
+                .unwrap_or(start);
+            self.lines
+                .insert(last + 1, self.line(format!("{} = {}", key, value)));
+        }
+        Ok(())
+    }
+
+    /// Returns false if the profile doesn't have the key.
+    pub fn unset(&mut self, profile_name: &str, key: &str) -> anyhow::Result<bool> {
+        let section = self
+            .section(profile_name)
+            .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", profile_name))?;
+        match self.property(section, key) {
+            Some((line, end)) => {
+                self.lines.drain(line..end);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Removes the section with the comments just above it.
+    /// Comments just above the next section are kept, since they belong to it.
+    pub fn remove_profile(&mut self, profile_name: &str) -> anyhow::Result<()> {
+        let (mut start, mut end) = self
+            .section(profile_name)
+            .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", profile_name))?;
+        let is_comment = |line: &str| !line.trim().is_empty() && is_blank_or_comment(line);
+        while start > 0 && is_comment(&self.lines[start - 1].text) {
+            start -= 1;
+        }
+        if end < self.lines.len() {
+            while end > start + 1 && is_comment(&self.lines[end - 1].text) {
+                end -= 1;
+            }
+        }
+        self.lines.drain(start..end);
+
+        // avoid leaving consecutive blank lines where the section was.
+        let blank =
+            |lines: &[Line], i: usize| lines.get(i).is_none_or(|l| l.text.trim().is_empty());
+        if start > 0 && blank(&self.lines, start - 1) && blank(&self.lines, start) {
+            self.lines.remove(start - 1);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# managed by hand\r\n\
+        [default]\r\n\
+        region = us-east-1 ; the home region\r\n\
+        \r\n\
+        # the dev account\r\n\
+        [profile dev]\r\n\
+        role_arn = arn:aws:iam::123456789012:role/Dev # read-only\r\n\
+        source_profile = default\r\n\
+        s3 =\r\n\
+        \x20 max_concurrent_requests = 10\r\n\
+        \r\n\
+        # the prod account\r\n\
+        [profile prod]\r\n\
+        region = eu-west-1\r\n";
+
+    fn edit(content: &str, f: impl FnOnce(&mut ConfigEditor)) -> String {
+        let mut editor = ConfigEditor::new(FileKind::Config, content);
+        f(&mut editor);
+        editor.content()
+    }
+
+    #[test]
+    fn content_is_kept_without_changes() {
+        assert_eq!(edit(CONFIG, |_| {}), CONFIG);
+        let no_newline = CONFIG.trim_end();
+        assert_eq!(edit(no_newline, |_| {}), no_newline);
+        let mixed = "[default]\r\nregion = us-east-1\n";
+        assert_eq!(edit(mixed, |_| {}), mixed);
+        assert_eq!(edit("", |_| {}), "");
+    }
+
+    #[test]
+    fn set_updates_the_value_in_place_keeping_the_comment() {
+        let updated = edit(CONFIG, |e| {
+            e.set("dev", "role_arn", "arn:aws:iam::123456789012:role/Admin")
+                .unwrap()
+        });
+        assert_eq!(
+            updated,
+            CONFIG.replace("role/Dev # read-only", "role/Admin # read-only")
+        );
+    }
+
+    #[test]
+    fn set_adds_the_key_at_the_end_of_the_section() {
+        let updated = edit(CONFIG, |e| {
+            e.set("dev", "region", "ap-northeast-1").unwrap()
+        });
+        assert_eq!(
+            updated,
+            CONFIG.replace(
+                "  max_concurrent_requests = 10\r\n",
+                "  max_concurrent_requests = 10\r\nregion = ap-northeast-1\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn set_fails_without_the_profile() {
+        let mut editor = ConfigEditor::new(FileKind::Config, CONFIG);
+        assert!(editor.set("staging", "region", "us-west-2").is_err());
+        assert_eq!(editor.content(), CONFIG);
+    }
+
+    #[test]
+    fn unset_removes_the_key_with_its_nested_lines() {
+        let mut removed = false;
+        let updated = edit(CONFIG, |e| removed = e.unset("dev", "s3").unwrap());
+        assert!(removed);
+        assert_eq!(
+            updated,
+            CONFIG.replace("s3 =\r\n  max_concurrent_requests = 10\r\n", "")
+        );
+
+        let mut editor = ConfigEditor::new(FileKind::Config, CONFIG);
+        assert!(!editor.unset("dev", "region").unwrap());
+        assert_eq!(editor.content(), CONFIG);
+    }
+
+    #[test]
+    fn add_profile_appends_a_section_with_the_line_ending_of_the_file() {
+        let updated = edit(CONFIG, |e| {
+            e.add_profile("staging").unwrap();
+            e.set("staging", "region", "us-west-2").unwrap();
+        });
+        assert_eq!(
+            updated,
+            format!("{}\r\n[profile staging]\r\nregion = us-west-2\r\n", CONFIG)
+        );
+
+        let updated = edit("", |e| e.add_profile("default").unwrap());
+        assert_eq!(updated, "[default]\n");
+
+        let mut editor = ConfigEditor::new(FileKind::Config, CONFIG);
+        assert!(editor.add_profile("dev").is_err());
+    }
+
+    #[test]
+    fn remove_profile_keeps_the_comments_of_the_next_section() {
+        let updated = edit(CONFIG, |e| e.remove_profile("dev").unwrap());
+        assert_eq!(
+            updated,
+            "# managed by hand\r\n\
+            [default]\r\n\
+            region = us-east-1 ; the home region\r\n\
+            \r\n\
+            # the prod account\r\n\
+            [profile prod]\r\n\
+            region = eu-west-1\r\n"
+        );
+    }
+
+    #[test]
+    fn the_last_duplicated_section_is_edited() {
+        let content = "[profile dev]\nregion = us-east-1\n\n[profile dev]\nregion = us-west-2\n";
+        let updated = edit(content, |e| e.set("dev", "region", "eu-west-1").unwrap());
+        assert_eq!(
+            updated,
+            "[profile dev]\nregion = us-east-1\n\n[profile dev]\nregion = eu-west-1\n"
+        );
+    }
+
+    #[test]
+    fn credentials_sections_have_no_prefix() {
+        let mut editor =
+            ConfigEditor::new(FileKind::Credentials, "[default]\naws_access_key_id = A\n");
+        editor.add_profile("ci").unwrap();
+        editor.set("ci", "aws_access_key_id", "B").unwrap();
+        assert_eq!(
+            editor.content(),
+            "[default]\naws_access_key_id = A\n\n[ci]\naws_access_key_id = B\n"
+        );
+    }
+
+    #[test]
+    fn assignment_requires_a_key() {
+        let a = " region = us-east-1 ".parse::<Assignment>().unwrap();
+        assert_eq!((a.key.as_str(), a.value.as_str()), ("region", "us-east-1"));
+        let a = "external_id=a=b".parse::<Assignment>().unwrap();
+        assert_eq!(a.value, "a=b");
+        assert!("region".parse::<Assignment>().is_err());
+        assert!("=us-east-1".parse::<Assignment>().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use aws_runtime::env_config::file::{EnvConfigFileKind, EnvConfigFiles};
+
+use crate::config::env_vars;
+use crate::dirs;
+use crate::profile::ini::IniFile;
+
+pub const CONFIG_FILE_FLAG: &str = "--config-file";
+pub const CREDENTIALS_FILE_FLAG: &str = "--credentials-file";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FileKind {
+    Config,
+    Credentials,
+}
+
+impl FileKind {
+    /// Returns the profile name of the section, e.g. `dev` for `[profile dev]` in the config file.
+    pub fn profile_name<'a>(&self, section_name: &'a str) -> Option<&'a str> {
+        match self {
+            FileKind::Config if section_name == "default" => Some("default"),
+            FileKind::Config => section_name
+                .strip_prefix("profile")
+                .filter(|s| s.starts_with(char::is_whitespace))
+                .map(|s| s.trim()),
+            FileKind::Credentials => Some(section_name),
+        }
+    }
+}
+
+/// The AWS config and credentials files to load.
+///
+/// Files are merged in this order, and the later ones take precedence:
+/// the default config file, the default credentials file, `ASSUME_ROLERS_CONFIG_FILES`,
+/// `--config-file` and then `--credentials-file`.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileFiles {
+    config: Vec<PathBuf>,
+    credentials: Vec<PathBuf>,
+}
+
+impl ProfileFiles {
+    /// Reads `ASSUME_ROLERS_CONFIG_FILES`, separated like `PATH`.
+    pub fn from_env() -> Self {
+        let config = env::var_os(env_vars::CONFIG_FILES)
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        ProfileFiles {
+            config,
+            credentials: Vec::default(),
+        }
+    }
+
+    /// Picks up the file flags before clap parses the arguments, since the profile names are
+    /// needed to build the command.
+    pub fn with_args<I: IntoIterator<Item = OsString>>(mut self, args: I) -> Self {
+        let mut args = args.into_iter().map(|s| s.to_string_lossy().to_string());
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            for (flag, files) in [
+                (CONFIG_FILE_FLAG, &mut self.config),
+                (CREDENTIALS_FILE_FLAG, &mut self.credentials),
+            ] {
+                if arg == flag {
+                    files.extend(args.next().map(PathBuf::from));
+                } else if let Some(path) = arg.strip_prefix(&format!("{}=", flag)) {
+                    files.push(PathBuf::from(path));
+                }
+            }
+        }
+        self
+    }
+
+    /// Files in the order of precedence, from the lowest.
+    pub fn files(&self) -> Vec<(FileKind, PathBuf)> {
+        let defaults = [
+            (FileKind::Config, dirs::aws_config_file()),
+            (FileKind::Credentials, dirs::aws_credentials_file()),
+        ];
+        defaults
+            .into_iter()
+            .filter_map(|(kind, path)| path.map(|p| (kind, p)))
+            .chain(self.config.iter().map(|p| (FileKind::Config, p.clone())))
+            .chain(
+                self.credentials
+                    .iter()
+                    .map(|p| (FileKind::Credentials, p.clone())),
+            )
+            .collect()
+    }
+
+    pub fn env_config_files(&self) -> EnvConfigFiles {
+        let mut builder = EnvConfigFiles::builder()
+            .include_default_config_file(true)
+            .include_default_credentials_file(true);
+        for path in self.config.iter() {
+            builder = builder.with_file(EnvConfigFileKind::Config, path);
+        }
+        for path in self.credentials.iter() {
+            builder = builder.with_file(EnvConfigFileKind::Credentials, path);
+        }
+        builder.build()
+    }
+
+    /// Scans all the files in the order of precedence. Missing files are skipped.
+    pub fn scan(&self) -> anyhow::Result<Vec<(FileKind, IniFile)>> {
+        let mut files = Vec::new();
+        for (kind, path) in self.files() {
+            if let Some(file) = IniFile::load(path)? {
+                files.push((kind, file));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Maps the profile names to the file which defines them with the highest precedence.
+    pub fn sources(&self) -> anyhow::Result<BTreeMap<String, PathBuf>> {
+        let mut sources = BTreeMap::new();
+        for (kind, file) in self.scan()? {
+            for section in file.sections.iter() {
+                if let Some(name) = kind.profile_name(&section.name) {
+                    sources.insert(name.to_string(), file.path.clone());
+                }
+            }
+        }
+        Ok(sources)
+    }
+}
@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::profile::Profile;
+
+/// A `key=value` pair matched against the profile tags.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Tag {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for Tag {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("tag must be in key=value format. tag:{}", s))?;
+        Ok(Tag {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Narrows down the profiles. Accounts and role names are alternatives, while all the other conditions must hold.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileFilter {
+    pub accounts: Vec<String>,
+    pub role_names: Vec<String>,
+    /// A regular expression matched against the profile name.
+    pub pattern: Option<Regex>,
+    pub tags: Vec<Tag>,
+    pub requires_mfa: bool,
+}
+
+/// Builds the filter from `--account`, `--role-name`, `--match`, `--tag` and `--requires-mfa`.
+impl From<&clap::ArgMatches> for ProfileFilter {
+    fn from(matches: &clap::ArgMatches) -> Self {
+        fn many<T: Clone + Send + Sync + 'static>(matches: &clap::ArgMatches, id: &str) -> Vec<T> {
+            matches
+                .get_many::<T>(id)
+                .map(|v| v.cloned().collect())
+                .unwrap_or_default()
+        }
+
+        ProfileFilter {
+            accounts: many(matches, "account"),
+            role_names: many(matches, "role-name"),
+            pattern: matches.get_one::<Regex>("match").cloned(),
+            tags: many(matches, "tag"),
+            requires_mfa: matches.get_flag("requires-mfa"),
+        }
+    }
+}
+
+impl ProfileFilter {
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.role_names.is_empty()
+            && self.pattern.is_none()
+            && self.tags.is_empty()
+            && !self.requires_mfa
+    }
+
+    pub fn matches(&self, profile: &Profile) -> bool {
+        let role = profile.role();
+        let by_account = self.accounts.is_empty()
+            || role
+                .as_ref()
+                .map(|r| self.accounts.contains(&r.account_id))
+                .unwrap_or(false);
+        let by_role_name = self.role_names.is_empty()
+            || role
+                .as_ref()
+                .map(|r| self.role_names.contains(&r.role_name))
+                .unwrap_or(false);
+        let by_pattern = self
+            .pattern
+            .as_ref()
+            .map(|re| re.is_match(profile.name()))
+            .unwrap_or(true);
+        let by_tags = self
+            .tags
+            .iter()
+            .all(|tag| profile.tag(&tag.key) == Some(tag.value.as_str()));
+        let by_mfa = !self.requires_mfa || profile.mfa_serial().is_some();
+
+        by_account && by_role_name && by_pattern && by_tags && by_mfa
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use similar::TextDiff;
+
+use crate::atomic;
+use crate::profile::files::FileKind;
+use crate::profile::ini::IniFile;
+
+pub mod cli;
+
+/// Names generated profiles from a template with placeholders, e.g. `{account_name}-{role_name}`.
+#[derive(Debug, Clone)]
+pub struct NameTemplate {
+    template: String,
+}
+
+impl NameTemplate {
+    pub fn new(template: &str, placeholders: &[&str]) -> anyhow::Result<Self> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow::anyhow!("unclosed placeholder. template:{}", template))?;
+            let name = &rest[start + 1..start + end];
+            if !placeholders.contains(&name) {
+                anyhow::bail!(
+                    "unknown placeholder. template:{}, placeholder:{}, available placeholders: {}",
+                    template,
+                    name,
+                    placeholders.join(", ")
+                );
+            }
+            rest = &rest[start + end + 1..];
+        }
+
+        Ok(NameTemplate {
+            template: template.to_string(),
+        })
+    }
+
+    /// Renders the name with the values made safe for a section name, e.g. `Acme Prod` to `acme-prod`.
+    /// A value without ASCII letters or digits, e.g. a Japanese account name, falls back to the account id.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let account_id = values
+            .iter()
+            .find(|(placeholder, _)| *placeholder == "account_id")
+            .map(|(_, value)| slug(value))
+            .unwrap_or_default();
+        let mut name = self.template.clone();
+        for (placeholder, value) in values {
+            let mut value = slug(value);
+            if value.is_empty() {
+                value = account_id.clone();
+            }
+            name = name.replace(&format!("{{{}}}", placeholder), &value);
+        }
+        name
+    }
+}
+
+fn slug(s: &str) -> String {
+    let mut slug = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A profile section to write to the AWS config file.
+#[derive(Debug, Clone)]
+pub struct GeneratedProfile {
+    pub name: String,
+    pub properties: Vec<(String, String)>,
+}
+
+/// Sections in the AWS config file which assume-rolers owns, and replaces as a whole on the next run.
+#[derive(Debug, Clone)]
+pub struct ManagedBlock {
+    name: String,
+    profiles: Vec<GeneratedProfile>,
+}
+
+impl ManagedBlock {
+    pub fn new(name: String) -> Self {
+        ManagedBlock {
+            name,
+            profiles: Vec::default(),
+        }
+    }
+
+    /// Adds the profile. The suffix is appended to the name if another profile in the block has it,
+    /// e.g. for accounts with the same name. Fails if the name is empty or still taken,
+    /// since the sections would be merged by the SDKs.
+    pub fn push(&mut self, mut profile: GeneratedProfile, suffix: &str) -> anyhow::Result<()> {
+        if profile.name.is_empty() {
+            anyhow::bail!(
+                "the generated profile name is empty. check --name-template. suffix:{}",
+                suffix
+            );
+        }
+        if self.profiles.iter().any(|p| p.name == profile.name) {
+            profile.name = format!("{}-{}", profile.name, suffix);
+        }
+        if self.profiles.iter().any(|p| p.name == profile.name) {
+            anyhow::bail!(
+                "duplicate generated profile name. add a placeholder to --name-template. profile:{}",
+                profile.name
+            );
+        }
+        self.profiles.push(profile);
+        Ok(())
+    }
+
+    fn begin_marker(&self) -> String {
+        format!("# BEGIN assume-rolers managed block: {}", self.name)
+    }
+
+    fn end_marker(&self) -> String {
+        format!("# END assume-rolers managed block: {}", self.name)
+    }
+
+    /// Returns the lines before and after the existing block, if any.
+    /// Fails on a block without the end marker, rather than appending another block.
+    fn split<'a>(
+        &self,
+        path: &Path,
+        content: &'a str,
+    ) -> anyhow::Result<(Vec<&'a str>, Option<Vec<&'a str>>)> {
+        let lines = content.lines().collect::<Vec<_>>();
+        let (begin, end) = (self.begin_marker(), self.end_marker());
+        let start = lines.iter().position(|l| l.trim() == begin);
+        let stop = start.and_then(|s| {
+            lines[s..]
+                .iter()
+                .position(|l| l.trim() == end)
+                .map(|n| s + n)
+        });
+        match (start, stop) {
+            (Some(start), Some(stop)) => Ok((
+                lines[..start].to_vec(),
+                Some(lines[stop + 1..].to_vec()),
+            )),
+            (Some(start), None) => Err(anyhow::anyhow!(
+                "the managed block has no end marker. add `{}` or remove the block. path:{}, line:{}",
+                end,
+                path.display(),
+                start + 1
+            )),
+            _ => Ok((lines, None)),
+        }
+    }
+
+    /// Renders the content with the block replaced, or appended if it doesn't exist yet.
+    /// Profiles defined outside of the block are kept, and skipped in the block.
+    /// The lines end with CRLF if the file's first line does.
+    pub fn merge(&self, path: &Path, content: &str) -> anyhow::Result<(String, Vec<String>)> {
+        let (before, after) = self.split(path, content)?;
+        let outside = before
+            .iter()
+            .chain(after.iter().flatten())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n");
+        let existing = IniFile::parse(path, &outside)
+            .sections
+            .iter()
+            .filter_map(|s| {
+                FileKind::Config
+                    .profile_name(&s.name)
+                    .map(|s| s.to_string())
+            })
+            .collect::<BTreeSet<_>>();
+
+        let mut block = String::new();
+        let mut skipped = Vec::new();
+        writeln!(block, "{}", self.begin_marker()).unwrap();
+        for profile in self.profiles.iter() {
+            if existing.contains(&profile.name) {
+                skipped.push(profile.name.to_string());
+                continue;
+            }
+            if !block.ends_with(&format!("{}\n", self.begin_marker())) {
+                block.push('\n');
+            }
+            if profile.name == "default" {
+                writeln!(block, "[default]").unwrap();
+            } else {
+                writeln!(block, "[profile {}]", profile.name).unwrap();
+            }
+            for (key, value) in profile.properties.iter() {
+                writeln!(block, "{} = {}", key, value).unwrap();
+            }
+        }
+        writeln!(block, "{}", self.end_marker()).unwrap();
+
+        let mut merged = String::new();
+        for line in before.iter() {
+            writeln!(merged, "{}", line).unwrap();
+        }
+        if after.is_none() && before.last().is_some_and(|l| !l.trim().is_empty()) {
+            merged.push('\n');
+        }
+        merged.push_str(&block);
+        for line in after.iter().flatten() {
+            writeln!(merged, "{}", line).unwrap();
+        }
+        let crlf = content
+            .find('\n')
+            .is_some_and(|i| content[..i].ends_with('\r'));
+        if crlf {
+            merged = merged.replace('\n', "\r\n");
+        }
+        Ok((merged, skipped))
+    }
+}
+
+/// A change to a config file, previewed as a diff before being written.
+#[derive(Debug, Clone)]
+pub struct ConfigUpdate {
+    pub path: PathBuf,
+    original: String,
+    updated: String,
+}
+
+impl ConfigUpdate {
+    /// Returns the update of the file by the function, which takes the current content.
+    pub fn with<P: AsRef<Path>, F: FnOnce(&str) -> anyhow::Result<String>>(
+        path: P,
+        f: F,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let original = Self::read(path)?;
+        let updated = f(&original)?;
+        Ok(ConfigUpdate {
+            path: path.to_path_buf(),
+            original,
+            updated,
+        })
+    }
+
+    fn read(path: &Path) -> anyhow::Result<String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the update of the file with the block, and the profiles skipped since they exist outside of it.
+    pub fn with_block<P: AsRef<Path>>(
+        path: P,
+        block: &ManagedBlock,
+    ) -> anyhow::Result<(Self, Vec<String>)> {
+        let path = path.as_ref();
+        let original = Self::read(path)?;
+        let (updated, skipped) = block.merge(path, &original)?;
+        let update = ConfigUpdate {
+            path: path.to_path_buf(),
+            original,
+            updated,
+        };
+        Ok((update, skipped))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.original == self.updated
+    }
+
+    /// A unified diff from the current content.
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        TextDiff::from_lines(&self.original, &self.updated)
+            .unified_diff()
+            .context_radius(3)
+            .header(&path, &path)
+            .to_string()
+    }
+
+    /// Replaces the file atomically, keeping its permissions.
+    /// A new file is readable only by the owner, since the credentials file has secrets.
+    pub fn apply(&self) -> anyhow::Result<()> {
+        atomic::write(
+            &self.path,
+            self.updated.as_bytes(),
+            atomic::mode_of(&self.path, 0o600),
+        )
+    }
+}
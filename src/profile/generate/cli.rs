@@ -0,0 +1,339 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use clap::builder::ValueParser;
+use clap::{ArgAction, ArgGroup};
+
+use crate::assume_role::aws_sdk::AwsSdkAssumeRole;
+use crate::config::Config;
+use crate::dirs;
+use crate::mfa::MfaReader;
+use crate::network::NetworkSettings;
+use crate::organizations::OrganizationsClient;
+use crate::profile::arn::RoleArn;
+use crate::profile::cli::confirm;
+use crate::profile::files::ProfileFiles;
+use crate::profile::generate::{ConfigUpdate, GeneratedProfile, ManagedBlock, NameTemplate};
+use crate::profile::load::aws_sdk::AwsSdkProfileLoader;
+use crate::profile::load::config::ConfigProfileLoader;
+use crate::profile::load::LoadProfiles;
+use crate::sso::{SsoClient, SsoSettings};
+
+const DISCOVER_NAME_TEMPLATE: &str = "{account_name}";
+const SSO_NAME_TEMPLATE: &str = "{account_name}-{role_name}";
+const PLACEHOLDERS: [&str; 3] = ["account_name", "account_id", "role_name"];
+
+/// Arguments of the commands which generate profiles into a config file.
+fn generate_args(default_template: &'static str) -> [clap::Arg; 5] {
+    [
+        clap::Arg::new("name-template")
+            .long("name-template")
+            .default_value(default_template)
+            .value_parser(|s: &str| NameTemplate::new(s, &PLACEHOLDERS))
+            .help(format!(
+                "Specify the profile names with the placeholders: {}.",
+                PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+            )),
+        clap::Arg::new("region")
+            .long("region")
+            .value_hint(clap::ValueHint::Other)
+            .help("Specify the region of the generated profiles."),
+        clap::Arg::new("file")
+            .long("file")
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_hint(clap::ValueHint::FilePath)
+            .help("Specify the config file to update. Defaults to the AWS config file."),
+        clap::Arg::new("yes")
+            .short('y')
+            .long("yes")
+            .action(ArgAction::SetTrue)
+            .help("Update the file without confirmation."),
+        clap::Arg::new("dry-run")
+            .long("dry-run")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("yes")
+            .help("Show the changes without updating the file."),
+    ]
+}
+
+#[derive(Debug)]
+pub struct Generate {
+    name_template: NameTemplate,
+    region: Option<String>,
+    file: Option<PathBuf>,
+    yes: bool,
+    dry_run: bool,
+}
+
+impl Generate {
+    /// Merges the block into the file, shows the diff, and writes it after confirmation.
+    fn update_config(self, block: &ManagedBlock) -> anyhow::Result<()> {
+        let path = self
+            .file
+            .or_else(dirs::aws_config_file)
+            .ok_or_else(|| anyhow::anyhow!("cannot find the AWS config file."))?;
+        let (update, skipped) = ConfigUpdate::with_block(path, block)?;
+        for name in skipped {
+            eprintln!(
+                "skipped the profile defined outside of the managed block. profile:{}",
+                name
+            );
+        }
+
+        if update.is_empty() {
+            eprintln!("no changes. path:{}", update.path.display());
+            return Ok(());
+        }
+        print!("{}", update.diff());
+        if self.dry_run {
+            return Ok(());
+        }
+
+        if !self.yes {
+            if !std::io::stdin().is_terminal() {
+                anyhow::bail!(
+                    "cannot confirm the changes in non-interactive mode. specify --yes or --dry-run."
+                );
+            }
+            if !confirm(&format!("apply the changes to {}?", update.path.display()))? {
+                eprintln!("canceled.");
+                return Ok(());
+            }
+        }
+
+        update.apply()?;
+        eprintln!("updated {}.", update.path.display());
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for Generate {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Generate {
+            name_template: m
+                .get_one::<NameTemplate>("name-template")
+                .expect("default value exists")
+                .clone(),
+            region: m.get_one::<String>("region").map(|s| s.to_string()),
+            file: m.get_one::<PathBuf>("file").cloned(),
+            yes: m.get_flag("yes"),
+            dry_run: m.get_flag("dry-run"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Discover {
+    via: String,
+    role: String,
+    endpoint_url: Option<reqwest::Url>,
+    token: Option<String>,
+    generate: Generate,
+}
+
+impl Discover {
+    /// `profile_names` parses `--via`, which may be any profile.
+    pub fn command(profile_names: ValueParser) -> clap::Command {
+        clap::Command::new("discover")
+            .about("Generate profiles for the accounts in the organization.")
+            .arg(
+                clap::Arg::new("via")
+                    .long("via")
+                    .required(true)
+                    .value_hint(clap::ValueHint::Other)
+                    .value_parser(profile_names)
+                    .help("Specify the profile of the management account, or a delegated administrator."),
+            )
+            .arg(
+                clap::Arg::new("role")
+                    .long("role")
+                    .required(true)
+                    .value_hint(clap::ValueHint::Other)
+                    .help("Specify the role to assume in each account, e.g. OrganizationAccountAccessRole."),
+            )
+            .args(generate_args(DISCOVER_NAME_TEMPLATE))
+            .arg(
+                clap::Arg::new("endpoint-url")
+                    .long("endpoint-url")
+                    .value_parser(|s: &str| s.parse::<reqwest::Url>())
+                    .value_hint(clap::ValueHint::Url)
+                    .help("Override the endpoint of the Organizations API, e.g. with a local stand-in."),
+            )
+            .arg(
+                clap::Arg::new("token")
+                    .short('t')
+                    .long("token")
+                    .value_hint(clap::ValueHint::Other)
+                    .help("Specify a token code provided by the MFA device."),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let profiles = ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone())
+            .load_profiles()
+            .await?;
+        let via = profiles
+            .get_profile(&self.via)
+            .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", self.via))?;
+        let mfa_reader = MfaReader::new(self.token.as_ref(), &config.mfa);
+        let network = NetworkSettings::from(&config.network);
+        let source = AwsSdkAssumeRole::from(&files)
+            .with_network(network.clone())
+            .profile_credentials(via, mfa_reader)
+            .await?;
+
+        // the accounts are in the partition of the management account.
+        let partition = via.partition();
+        let network = network.with_profile_ca_bundle(via.endpoint.ca_bundle.as_deref());
+        let mut client = OrganizationsClient::new(source.credentials, partition, network)?;
+        if let Some(endpoint) = self.endpoint_url {
+            client = client.with_endpoint(endpoint);
+        }
+        let mut accounts = client.list_accounts().await?;
+        // ListAccounts has no order, which would reorder the block on every run.
+        accounts.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+        let active = accounts
+            .iter()
+            .filter(|a| a.is_active())
+            .collect::<Vec<_>>();
+        eprintln!(
+            "found {} active account(s) out of {}.",
+            active.len(),
+            accounts.len()
+        );
+
+        let mut block = ManagedBlock::new(format!("discover {}/{}", self.via, self.role));
+        for account in active {
+            let name = self.generate.name_template.render(&[
+                ("account_name", &account.name),
+                ("account_id", &account.id),
+                ("role_name", &self.role),
+            ]);
+            let mut properties = vec![
+                (
+                    "role_arn".to_string(),
+                    RoleArn::new(partition, &account.id, &self.role)?.to_string(),
+                ),
+                ("source_profile".to_string(), self.via.to_string()),
+            ];
+            if let Some(region) = self.generate.region.as_ref() {
+                properties.push(("region".to_string(), region.to_string()));
+            }
+            block.push(GeneratedProfile { name, properties }, &account.id)?;
+        }
+        self.generate.update_config(&block)
+    }
+}
+
+impl From<&clap::ArgMatches> for Discover {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Discover {
+            via: m.get_one::<String>("via").expect("required").to_string(),
+            role: m.get_one::<String>("role").expect("required").to_string(),
+            endpoint_url: m.get_one::<reqwest::Url>("endpoint-url").cloned(),
+            token: m.get_one::<String>("token").map(|s| s.to_string()),
+            generate: Generate::from(m),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SsoPopulate {
+    sso_session: Option<String>,
+    profile: Option<String>,
+    generate: Generate,
+}
+
+impl SsoPopulate {
+    /// `profile_names` parses `--profile`, which may be any profile.
+    pub fn command(profile_names: ValueParser) -> clap::Command {
+        clap::Command::new("sso")
+            .about("Manage the profiles for IAM Identity Center.")
+            .subcommand_required(true)
+            .subcommand(
+                clap::Command::new("populate")
+                    .about("Generate profiles for the accounts and roles you can access, with the token cached by `aws sso login`.")
+                    .arg(
+                        clap::Arg::new("sso-session")
+                            .long("sso-session")
+                            .value_hint(clap::ValueHint::Other)
+                            .help("Specify the sso-session section in the AWS config."),
+                    )
+                    .arg(
+                        clap::Arg::new("profile")
+                            .long("profile")
+                            .value_hint(clap::ValueHint::Other)
+                            .value_parser(profile_names)
+                            .help("Specify an SSO profile to take the access portal from."),
+                    )
+                    .group(
+                        ArgGroup::new("portal")
+                            .args(["sso-session", "profile"])
+                            .required(true),
+                    )
+                    .args(generate_args(SSO_NAME_TEMPLATE)),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let scanned = files.scan()?;
+        let settings = match (self.sso_session, self.profile) {
+            (Some(session_name), _) => SsoSettings::from_session(&scanned, &session_name)?,
+            (None, Some(profile_name)) => SsoSettings::from_profile(&scanned, &profile_name)?,
+            (None, None) => unreachable!("either is required"),
+        };
+        let client = SsoClient::new(
+            &settings,
+            settings.cached_token()?,
+            &NetworkSettings::from(&config.network),
+        )
+        .await?;
+
+        let mut accounts = client.list_accounts().await?;
+        // the same order on every run, so that the block changes only if the listing does.
+        accounts.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+        let generate = self.generate;
+        let mut block = ManagedBlock::new(format!(
+            "sso {}",
+            settings
+                .session_name
+                .as_ref()
+                .unwrap_or(&settings.start_url)
+        ));
+        let mut count = 0;
+        for account in accounts.iter() {
+            let mut role_names = client.list_account_roles(&account.id).await?;
+            role_names.sort();
+            for role_name in role_names {
+                let name = generate.name_template.render(&[
+                    ("account_name", &account.name),
+                    ("account_id", &account.id),
+                    ("role_name", &role_name),
+                ]);
+                let mut properties = settings.profile_properties();
+                properties.push(("sso_account_id".to_string(), account.id.to_string()));
+                properties.push(("sso_role_name".to_string(), role_name.to_string()));
+                if let Some(region) = generate.region.as_ref() {
+                    properties.push(("region".to_string(), region.to_string()));
+                }
+                block.push(GeneratedProfile { name, properties }, &account.id)?;
+                count += 1;
+            }
+        }
+        eprintln!("found {} role(s) in {} account(s).", count, accounts.len());
+        generate.update_config(&block)
+    }
+}
+
+impl From<&clap::ArgMatches> for SsoPopulate {
+    fn from(m: &clap::ArgMatches) -> Self {
+        let m = m
+            .subcommand_matches("populate")
+            .expect("subcommand is required");
+        SsoPopulate {
+            sso_session: m.get_one::<String>("sso-session").map(|s| s.to_string()),
+            profile: m.get_one::<String>("profile").map(|s| s.to_string()),
+            generate: Generate::from(m),
+        }
+    }
+}
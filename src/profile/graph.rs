@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::profile::{Profile, ProfileKind, ProfileSet};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Tree,
+}
+
+impl GraphFormat {
+    pub const NAMES: [&'static str; 3] = ["tree", "dot", "mermaid"];
+}
+
+impl FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "tree" => Ok(GraphFormat::Tree),
+            _ => Err(anyhow::anyhow!(
+                "unknown graph format. format:{}, available formats: {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Relationships between the profiles by `source_profile`, drawn from the source to the role.
+pub struct ProfileGraph<'a> {
+    nodes: Vec<&'a Profile>,
+    /// Profiles which use the key as their source.
+    children: BTreeMap<&'a str, Vec<&'a str>>,
+    highlight: Vec<&'a str>,
+}
+
+impl<'a> From<&'a ProfileSet> for ProfileGraph<'a> {
+    fn from(profiles: &'a ProfileSet) -> Self {
+        let mut children = BTreeMap::<&str, Vec<&str>>::new();
+        for profile in profiles.profiles() {
+            if let Some(source) = Self::source_of(profiles, profile) {
+                children.entry(source).or_default().push(profile.name());
+            }
+        }
+        // profiles which neither assume a role nor relate to others are irrelevant.
+        let nodes = profiles
+            .profiles()
+            .filter(|p| {
+                p.has_role_arn()
+                    || children.contains_key(p.name())
+                    || Self::source_of(profiles, p).is_some()
+            })
+            .collect();
+
+        ProfileGraph {
+            nodes,
+            children,
+            highlight: Vec::default(),
+        }
+    }
+}
+
+impl<'a> ProfileGraph<'a> {
+    fn source_of(profiles: &'a ProfileSet, profile: &'a Profile) -> Option<&'a str> {
+        profile
+            .source_profile_name()
+            .filter(|&name| name != profile.name())
+            .and_then(|name| profiles.get_profile(name))
+            .map(|p| p.name())
+    }
+
+    /// Highlights the path used to assume the profile, from its base credentials.
+    pub fn with_highlight(mut self, profiles: &'a ProfileSet, profile_name: &str) -> Self {
+        let mut chain = profiles
+            .source_chain(profile_name)
+            .into_iter()
+            .map(|p| p.name())
+            .collect::<Vec<_>>();
+        chain.reverse();
+        self.highlight = chain;
+        self
+    }
+
+    fn is_highlighted(&self, name: &str) -> bool {
+        self.highlight.contains(&name)
+    }
+
+    fn is_highlighted_edge(&self, source: &str, target: &str) -> bool {
+        self.highlight
+            .windows(2)
+            .any(|w| w[0] == source && w[1] == target)
+    }
+
+    fn annotations(profile: &Profile) -> Vec<String> {
+        let mut annotations = Vec::new();
+        if let Some(role) = profile.role() {
+            annotations.push(role.account_id);
+        }
+        if profile.kind != ProfileKind::Role {
+            annotations.push(profile.kind.to_string());
+        }
+        if let Some(credential_source) = profile.credential_source() {
+            annotations.push(credential_source.to_string());
+        }
+        if profile.mfa_serial().is_some() {
+            annotations.push("mfa".to_string());
+        }
+        annotations
+    }
+
+    fn edges(&self) -> Vec<(&'a str, &'a str)> {
+        self.children
+            .iter()
+            .flat_map(|(source, targets)| targets.iter().map(move |target| (*source, *target)))
+            .collect()
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+            GraphFormat::Tree => self.tree(),
+        }
+    }
+
+    fn dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let quote = |s: &str| format!("\"{}\"", escape(s));
+        let mut out = String::new();
+        writeln!(out, "digraph profiles {{").unwrap();
+        writeln!(out, "  rankdir=LR;").unwrap();
+        writeln!(out, "  node [shape=box];").unwrap();
+        for profile in self.nodes.iter() {
+            let label = [profile.name().to_string()]
+                .into_iter()
+                .chain(Self::annotations(profile))
+                .map(|s| escape(&s))
+                .collect::<Vec<_>>()
+                .join("\\n");
+            let style = if self.is_highlighted(profile.name()) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "  {} [label=\"{}\"{}];",
+                quote(profile.name()),
+                label,
+                style
+            )
+            .unwrap();
+        }
+        for (source, target) in self.edges() {
+            let style = if self.is_highlighted_edge(source, target) {
+                " [color=red, penwidth=2]"
+            } else {
+                ""
+            };
+            writeln!(out, "  {} -> {}{};", quote(source), quote(target), style).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        // profile names may contain characters which mermaid doesn't accept as IDs.
+        let ids = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.name(), format!("p{}", i)))
+            .collect::<BTreeMap<_, _>>();
+        let mut out = String::new();
+        writeln!(out, "flowchart LR").unwrap();
+        for profile in self.nodes.iter() {
+            let label = [profile.name().to_string()]
+                .into_iter()
+                .chain(Self::annotations(profile))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            writeln!(
+                out,
+                "  {}[\"{}\"]",
+                ids[profile.name()],
+                label.replace('"', "#quot;")
+            )
+            .unwrap();
+        }
+        let mut highlighted_edges = Vec::new();
+        for (i, (source, target)) in self.edges().into_iter().enumerate() {
+            writeln!(out, "  {} --> {}", ids[source], ids[target]).unwrap();
+            if self.is_highlighted_edge(source, target) {
+                highlighted_edges.push(i.to_string());
+            }
+        }
+        if !self.highlight.is_empty() {
+            writeln!(out, "  classDef highlight stroke:#f00,stroke-width:3px").unwrap();
+            let highlighted = self
+                .highlight
+                .iter()
+                .filter_map(|name| ids.get(name))
+                .cloned()
+                .collect::<Vec<_>>();
+            writeln!(out, "  class {} highlight", highlighted.join(",")).unwrap();
+        }
+        if !highlighted_edges.is_empty() {
+            writeln!(
+                out,
+                "  linkStyle {} stroke:#f00,stroke-width:3px",
+                highlighted_edges.join(",")
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    fn tree(&self) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|p| (p.name(), *p))
+            .collect::<BTreeMap<_, _>>();
+        let has_parent = self
+            .children
+            .values()
+            .flatten()
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        let mut out = String::new();
+        let mut visited = BTreeSet::new();
+        let roots = self
+            .nodes
+            .iter()
+            .map(|p| p.name())
+            .filter(|name| !has_parent.contains(name))
+            .collect::<Vec<_>>();
+        for root in roots {
+            self.tree_node(&mut out, &nodes, &mut visited, root, "", None);
+        }
+        // profiles in a cycle have no root.
+        for profile in self.nodes.iter() {
+            if !visited.contains(profile.name()) {
+                self.tree_node(&mut out, &nodes, &mut visited, profile.name(), "", None);
+            }
+        }
+        out
+    }
+
+    /// `last` is `None` for roots, otherwise whether the node is the last child.
+    fn tree_node(
+        &self,
+        out: &mut String,
+        nodes: &BTreeMap<&str, &Profile>,
+        visited: &mut BTreeSet<&'a str>,
+        name: &'a str,
+        prefix: &str,
+        last: Option<bool>,
+    ) {
+        let (branch, child_prefix) = match last {
+            None => ("", prefix.to_string()),
+            Some(true) => ("└── ", format!("{}    ", prefix)),
+            Some(false) => ("├── ", format!("{}│   ", prefix)),
+        };
+        let marker = if self.is_highlighted(name) { "* " } else { "" };
+        let annotations = nodes
+            .get(name)
+            .map(|p| Self::annotations(p))
+            .unwrap_or_default();
+        let annotations = if annotations.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", annotations.join(", "))
+        };
+
+        if !visited.insert(name) {
+            writeln!(out, "{}{}{}{} (cycle)", prefix, branch, marker, name).unwrap();
+            return;
+        }
+        writeln!(out, "{}{}{}{}{}", prefix, branch, marker, name, annotations).unwrap();
+
+        let children = self.children.get(name).cloned().unwrap_or_default();
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            self.tree_node(out, nodes, visited, child, &child_prefix, Some(last));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A `key = value` line. Indented lines under an empty value are its children, e.g. `s3 =`.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub key: String,
+    pub value: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub children: Vec<Property>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// The name between the brackets, e.g. `profile dev`.
+    pub name: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub properties: Vec<Property>,
+}
+
+impl Section {
+    pub fn get(&self, key: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.key == key)
+    }
+}
+
+/// An AWS config or credentials file scanned with line numbers, without failing on malformed lines.
+#[derive(Debug, Clone)]
+pub struct IniFile {
+    pub path: PathBuf,
+    pub sections: Vec<Section>,
+    /// Lines which couldn't be scanned, with the reason.
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Strips a comment which starts with `#` or `;` after a whitespace.
+pub fn strip_comment(s: &str) -> &str {
+    let mut prev_whitespace = false;
+    for (i, c) in s.char_indices() {
+        if (c == '#' || c == ';') && prev_whitespace {
+            return &s[..i];
+        }
+        prev_whitespace = c.is_whitespace();
+    }
+    s
+}
+
+/// Returns the name of the section if the line is a header, e.g. `profile dev` for `[profile dev]`.
+pub fn section_header(line: &str) -> Option<&str> {
+    let header = strip_comment(line.trim()).trim();
+    header
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+}
+
+fn property_from(line: &str, number: usize) -> Option<Property> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() {
+        return None;
+    }
+    Some(Property {
+        key: key.to_string(),
+        value: value.trim().to_string(),
+        line: number,
+        children: Vec::default(),
+    })
+}
+
+impl IniFile {
+    /// Returns `None` if the file doesn't exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Option<IniFile>> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(Self::parse(path, &content))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse<P: AsRef<Path>>(path: P, content: &str) -> IniFile {
+        let mut sections = Vec::<Section>::new();
+        let mut errors = Vec::new();
+
+        for (i, raw) in content.lines().enumerate() {
+            let number = i + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                continue;
+            }
+
+            if trimmed.starts_with('[') {
+                match section_header(trimmed) {
+                    Some(name) => sections.push(Section {
+                        name: name.to_string(),
+                        line: number,
+                        properties: Vec::default(),
+                    }),
+                    None => errors.push((number, format!("malformed section header: {}", trimmed))),
+                }
+                continue;
+            }
+
+            let Some(section) = sections.last_mut() else {
+                errors.push((number, "property outside of any section.".to_string()));
+                continue;
+            };
+
+            let indented = raw.starts_with(char::is_whitespace);
+            let line = strip_comment(trimmed).trim();
+            match section.properties.last_mut() {
+                // a child of the previous property with an empty value.
+                Some(parent) if indented && parent.value.is_empty() => {
+                    match property_from(line, number) {
+                        Some(child) => parent.children.push(child),
+                        None => errors.push((number, format!("malformed property: {}", line))),
+                    }
+                }
+                // a continuation of the previous value.
+                Some(parent) if indented => {
+                    parent.value.push('\n');
+                    parent.value.push_str(line);
+                }
+                _ => match property_from(line, number) {
+                    Some(property) => section.properties.push(property),
+                    None => errors.push((number, format!("malformed property: {}", line))),
+                },
+            }
+        }
+
+        IniFile {
+            path: path.as_ref().to_path_buf(),
+            sections,
+            errors,
+        }
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::partition::Partition;
+use crate::profile::arn::{is_account_id, RoleArn};
+use crate::profile::files::FileKind;
+use crate::profile::ini::{IniFile, Property, Section};
+
+/// Keys documented for the AWS CLI and SDKs, and the ones assume-rolers reads.
+pub const KNOWN_KEYS: &[&str] = &[
+    "account_id_endpoint_mode",
+    "api_versions",
+    "auth_scheme_preference",
+    "aws_access_key_id",
+    "aws_account_id",
+    "aws_secret_access_key",
+    "aws_session_token",
+    "ca_bundle",
+    "cli_auto_prompt",
+    "cli_binary_format",
+    "cli_follow_urlparam",
+    "cli_history",
+    "cli_pager",
+    "cli_timestamp_format",
+    "credential_process",
+    "credential_source",
+    "csm_client_id",
+    "csm_enabled",
+    "csm_host",
+    "csm_port",
+    "defaults_mode",
+    "disable_request_compression",
+    "duration_seconds",
+    "ec2_metadata_service_endpoint",
+    "ec2_metadata_service_endpoint_mode",
+    "ec2_metadata_v1_disabled",
+    "endpoint_discovery_enabled",
+    "endpoint_url",
+    "external_id",
+    "ignore_configured_endpoint_urls",
+    "max_attempts",
+    "metadata_service_num_attempts",
+    "metadata_service_timeout",
+    "mfa_serial",
+    "output",
+    "parameter_validation",
+    "region",
+    "request_checksum_calculation",
+    "request_min_compression_size_bytes",
+    "response_checksum_validation",
+    "retry_mode",
+    "role_arn",
+    "role_session_name",
+    "s3",
+    "s3api",
+    "scope_down_policy",
+    "sdk_ua_app_id",
+    "services",
+    "sigv4a_signing_region_set",
+    "source_profile",
+    "sso_account_id",
+    "sso_region",
+    "sso_registration_scopes",
+    "sso_role_name",
+    "sso_session",
+    "sso_start_url",
+    "sts_regional_endpoints",
+    "tcp_keepalive",
+    "use_dualstack_endpoint",
+    "use_fips_endpoint",
+    "web_identity_token_file",
+];
+
+const MIN_DURATION_SECONDS: u32 = 900;
+const MAX_DURATION_SECONDS: u32 = 43200;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub path: PathBuf,
+    pub line: usize,
+    pub severity: Severity,
+    pub profile: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.path.display(),
+            self.line,
+            self.severity,
+            self.message
+        )?;
+        if let Some(profile) = self.profile.as_ref() {
+            write!(f, " (profile:{})", profile)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sections of a profile across the config and credentials files.
+struct LintProfile<'a> {
+    name: String,
+    sections: Vec<(&'a Path, &'a Section)>,
+}
+
+impl<'a> LintProfile<'a> {
+    /// Values in the file with the higher precedence win, as it's scanned later.
+    fn get(&self, key: &str) -> Option<(&'a Path, &'a Property)> {
+        self.sections
+            .iter()
+            .rev()
+            .find_map(|(path, section)| section.get(key).map(|p| (*path, p)))
+    }
+
+    fn value(&self, key: &str) -> Option<&'a str> {
+        self.get(key).map(|(_, p)| p.value.as_str())
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn header(&self) -> (&'a Path, usize) {
+        let (path, section) = self.sections[0];
+        (path, section.line)
+    }
+}
+
+pub fn is_mfa_serial(s: &str) -> bool {
+    if s.starts_with("arn:") {
+        let parts = s.splitn(6, ':').collect::<Vec<_>>();
+        let [_, partition, service, region, account_id, resource] = parts[..] else {
+            return false;
+        };
+        partition.parse::<Partition>().is_ok()
+            && service == "iam"
+            && region.is_empty()
+            && is_account_id(account_id)
+            && (resource.starts_with("mfa/") || resource.starts_with("u2f/"))
+    } else {
+        // serial numbers of hardware devices.
+        (9..=256).contains(&s.len())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+=,.@:/-_".contains(c))
+    }
+}
+
+/// Checks the profiles in the AWS config and credentials files, and reports all the problems found.
+pub struct Linter<'a> {
+    /// Files in the order of precedence, from the lowest.
+    files: &'a [(FileKind, IniFile)],
+    problems: Vec<Problem>,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(files: &'a [(FileKind, IniFile)]) -> Self {
+        Linter {
+            files,
+            problems: Vec::default(),
+        }
+    }
+
+    fn report<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        line: usize,
+        severity: Severity,
+        profile: Option<&str>,
+        message: String,
+    ) {
+        self.problems.push(Problem {
+            path: path.as_ref().to_path_buf(),
+            line,
+            severity,
+            profile: profile.map(|s| s.to_string()),
+            message,
+        });
+    }
+
+    pub fn lint(mut self) -> Vec<Problem> {
+        let (profiles, sso_sessions) = self.collect();
+        for profile in profiles.values() {
+            self.lint_keys(profile);
+            self.lint_role(profile, &profiles);
+            self.lint_duration(profile);
+            self.lint_mfa_serial(profile);
+            self.lint_endpoint(profile);
+            self.lint_sso(profile, &sso_sessions);
+        }
+        self.lint_cycles(&profiles);
+
+        self.problems
+            .sort_by(|a, b| (&a.path, a.line, a.severity).cmp(&(&b.path, b.line, b.severity)));
+        self.problems
+    }
+
+    fn collect(
+        &mut self,
+    ) -> (
+        BTreeMap<String, LintProfile<'a>>,
+        BTreeMap<String, &'a Section>,
+    ) {
+        let mut profiles = BTreeMap::<String, LintProfile<'a>>::new();
+        let mut sso_sessions = BTreeMap::new();
+        let mut add = |name: &str, path: &'a Path, section: &'a Section| {
+            profiles
+                .entry(name.to_string())
+                .or_insert_with(|| LintProfile {
+                    name: name.to_string(),
+                    sections: Vec::default(),
+                })
+                .sections
+                .push((path, section));
+        };
+
+        for (kind, file) in self.files.iter() {
+            for (line, message) in file.errors.iter() {
+                self.report(&file.path, *line, Severity::Error, None, message.clone());
+            }
+            if *kind == FileKind::Credentials {
+                for section in file.sections.iter() {
+                    if section.name.starts_with("profile ") {
+                        self.report(
+                            &file.path,
+                            section.line,
+                            Severity::Warning,
+                            None,
+                            format!(
+                                "the credentials file doesn't use the `profile` prefix. profile:{}",
+                                section.name
+                            ),
+                        );
+                    }
+                    add(&section.name, &file.path, section);
+                }
+                continue;
+            }
+
+            for section in file.sections.iter() {
+                let mut words = section.name.split_whitespace();
+                match (words.next(), words.next(), words.next()) {
+                    (Some("default"), None, _) => add("default", &file.path, section),
+                    (Some("profile"), Some(name), None) => add(name, &file.path, section),
+                    (Some("sso-session"), Some(name), None) => {
+                        sso_sessions.insert(name.to_string(), section);
+                    }
+                    (Some("services"), Some(_), None) => {}
+                    (Some(name), None, _) => self.report(
+                        &file.path,
+                        section.line,
+                        Severity::Warning,
+                        None,
+                        format!(
+                            "section is ignored in the config file. use [profile {}] instead.",
+                            name
+                        ),
+                    ),
+                    _ => self.report(
+                        &file.path,
+                        section.line,
+                        Severity::Warning,
+                        None,
+                        format!("unknown section: [{}]", section.name),
+                    ),
+                }
+            }
+        }
+
+        (profiles, sso_sessions)
+    }
+
+    fn lint_keys(&mut self, profile: &LintProfile<'a>) {
+        for (path, section) in profile.sections.iter() {
+            let mut seen = BTreeSet::new();
+            for property in section.properties.iter() {
+                if !KNOWN_KEYS.contains(&property.key.as_str()) {
+                    self.report(
+                        path,
+                        property.line,
+                        Severity::Warning,
+                        Some(&profile.name),
+                        format!("unknown key: {}", property.key),
+                    );
+                }
+                if !seen.insert(property.key.as_str()) {
+                    self.report(
+                        path,
+                        property.line,
+                        Severity::Warning,
+                        Some(&profile.name),
+                        format!("duplicated key: {}", property.key),
+                    );
+                }
+            }
+        }
+    }
+
+    fn lint_role(&mut self, profile: &LintProfile<'a>, profiles: &BTreeMap<String, LintProfile>) {
+        let name = Some(profile.name.as_str());
+        if let Some((path, property)) = profile.get("source_profile") {
+            if !profiles.contains_key(&property.value) {
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    name,
+                    format!("source profile not found: {}", property.value),
+                );
+            }
+            if profile.has("credential_source") {
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    name,
+                    "source_profile and credential_source are mutually exclusive.".to_string(),
+                );
+            }
+        }
+
+        let Some((path, property)) = profile.get("role_arn") else {
+            return;
+        };
+        if let Err(e) = property.value.parse::<RoleArn>() {
+            self.report(path, property.line, Severity::Error, name, e.to_string());
+        }
+        let has_source = [
+            "source_profile",
+            "credential_source",
+            "web_identity_token_file",
+        ]
+        .iter()
+        .any(|key| profile.has(key));
+        if !has_source {
+            self.report(
+                path,
+                property.line,
+                Severity::Error,
+                name,
+                "role_arn requires source_profile, credential_source or web_identity_token_file."
+                    .to_string(),
+            );
+        }
+    }
+
+    fn lint_duration(&mut self, profile: &LintProfile<'a>) {
+        let Some((path, property)) = profile.get("duration_seconds") else {
+            return;
+        };
+        let message = match property.value.parse::<u32>() {
+            Ok(n) if (MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&n) => return,
+            Ok(n) => format!(
+                "duration_seconds must be between {} and {}. duration_seconds:{}",
+                MIN_DURATION_SECONDS, MAX_DURATION_SECONDS, n
+            ),
+            Err(_) => format!(
+                "duration_seconds must be a number. duration_seconds:{}",
+                property.value
+            ),
+        };
+        self.report(
+            path,
+            property.line,
+            Severity::Error,
+            Some(&profile.name),
+            message,
+        );
+    }
+
+    fn lint_mfa_serial(&mut self, profile: &LintProfile<'a>) {
+        let Some((path, property)) = profile.get("mfa_serial") else {
+            return;
+        };
+        if !is_mfa_serial(&property.value) {
+            self.report(
+                path,
+                property.line,
+                Severity::Error,
+                Some(&profile.name),
+                format!("invalid MFA serial. mfa_serial:{}", property.value),
+            );
+        }
+    }
+
+    fn lint_endpoint(&mut self, profile: &LintProfile<'a>) {
+        for key in [
+            "use_fips_endpoint",
+            "use_dualstack_endpoint",
+            "ignore_configured_endpoint_urls",
+        ] {
+            let Some((path, property)) = profile.get(key) else {
+                continue;
+            };
+            if !matches!(property.value.to_lowercase().as_str(), "true" | "false") {
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    Some(&profile.name),
+                    format!("{} must be true or false. {}:{}", key, key, property.value),
+                );
+            }
+        }
+    }
+
+    fn lint_sso(&mut self, profile: &LintProfile<'a>, sso_sessions: &BTreeMap<String, &Section>) {
+        let name = Some(profile.name.as_str());
+        let (path, line) = profile.header();
+        let is_sso = [
+            "sso_session",
+            "sso_start_url",
+            "sso_account_id",
+            "sso_role_name",
+        ]
+        .iter()
+        .any(|key| profile.has(key));
+        if !is_sso {
+            return;
+        }
+
+        for other in ["aws_access_key_id", "credential_process", "source_profile"] {
+            if let Some((path, property)) = profile.get(other) {
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    name,
+                    format!("{} conflicts with the SSO settings.", other),
+                );
+            }
+        }
+        if profile.has("sso_account_id") != profile.has("sso_role_name") {
+            self.report(
+                path,
+                line,
+                Severity::Error,
+                name,
+                "sso_account_id and sso_role_name must be specified together.".to_string(),
+            );
+        }
+
+        if let Some((path, property)) = profile.get("sso_session") {
+            let session_name = property.value.as_str();
+            let Some(session) = sso_sessions.get(session_name) else {
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    name,
+                    format!("sso-session not found: {}", property.value),
+                );
+                return;
+            };
+            for key in ["sso_start_url", "sso_region"] {
+                let expected = session.get(key).map(|p| p.value.as_str());
+                if let Some((path, property)) = profile.get(key) {
+                    if expected.is_some() && expected != Some(property.value.as_str()) {
+                        self.report(
+                            path,
+                            property.line,
+                            Severity::Error,
+                            name,
+                            format!(
+                                "{} conflicts with the sso-session. sso_session:{}",
+                                key, session_name
+                            ),
+                        );
+                    }
+                }
+            }
+        } else if profile.has("sso_start_url") || profile.has("sso_account_id") {
+            // the legacy settings without sso-session.
+            for key in [
+                "sso_start_url",
+                "sso_region",
+                "sso_account_id",
+                "sso_role_name",
+            ] {
+                if !profile.has(key) {
+                    self.report(
+                        path,
+                        line,
+                        Severity::Error,
+                        name,
+                        format!("{} is required for the SSO profile.", key),
+                    );
+                }
+            }
+        }
+        if let Some(account_id) = profile.value("sso_account_id") {
+            if !is_account_id(account_id) {
+                let (path, property) = profile.get("sso_account_id").expect("key exists");
+                self.report(
+                    path,
+                    property.line,
+                    Severity::Error,
+                    name,
+                    format!("invalid account ID. sso_account_id:{}", account_id),
+                );
+            }
+        }
+    }
+
+    fn lint_cycles(&mut self, profiles: &BTreeMap<String, LintProfile<'a>>) {
+        let mut reported = BTreeSet::new();
+        for start in profiles.values() {
+            let mut chain = vec![start.name.as_str()];
+            let mut current = start;
+            while let Some(next) = current.value("source_profile") {
+                // a profile can be its own source when it has static keys.
+                if next == current.name && current.has("aws_access_key_id") {
+                    break;
+                }
+                if let Some(i) = chain.iter().position(|n| *n == next) {
+                    let mut members = chain[i..].to_vec();
+                    members.sort();
+                    if reported.insert(members) {
+                        let (path, property) = current.get("source_profile").expect("key exists");
+                        let mut cycle = chain[i..].to_vec();
+                        cycle.push(next);
+                        self.report(
+                            path,
+                            property.line,
+                            Severity::Error,
+                            Some(&current.name),
+                            format!("source_profile forms a cycle: {}", cycle.join(" -> ")),
+                        );
+                    }
+                    break;
+                }
+                let Some(profile) = profiles.get(next) else {
+                    break;
+                };
+                chain.push(next);
+                current = profile;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(config: &str, credentials: &str) -> Vec<String> {
+        let files = [
+            (FileKind::Config, IniFile::parse("config", config)),
+            (
+                FileKind::Credentials,
+                IniFile::parse("credentials", credentials),
+            ),
+        ];
+        Linter::new(&files)
+            .lint()
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    const CREDENTIALS: &str = "[base]\naws_access_key_id = A\naws_secret_access_key = S\n";
+
+    #[test]
+    fn known_keys_are_accepted() {
+        let config = "[profile dev]\n\
+            role_arn = arn:aws:iam::123456789012:role/Dev\n\
+            source_profile = base\n\
+            region = us-east-1\n\
+            duration_seconds = 3600\n\
+            mfa_serial = arn:aws:iam::123456789012:mfa/alice\n\
+            ignore_configured_endpoint_urls = true\n\
+            use_fips_endpoint = false\n";
+        assert_eq!(lint(config, CREDENTIALS), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_and_duplicated_keys_are_warned() {
+        let config = "[profile dev]\nregoin = us-east-1\nregion = us-east-1\nregion = us-west-2\n";
+        assert_eq!(
+            lint(config, CREDENTIALS),
+            [
+                "config:2: warning: unknown key: regoin (profile:dev)",
+                "config:4: warning: duplicated key: region (profile:dev)",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let config = "[profile dev]\n\
+            role_arn = arn:aws-xx:iam::123456789012:role/Dev\n\
+            source_profile = base\n\
+            duration_seconds = 60\n\
+            use_fips_endpoint = yes\n";
+        assert_eq!(
+            lint(config, CREDENTIALS),
+            [
+                "config:2: error: invalid role ARN. arn:arn:aws-xx:iam::123456789012:role/Dev (profile:dev)",
+                "config:4: error: duration_seconds must be between 900 and 43200. duration_seconds:60 (profile:dev)",
+                "config:5: error: use_fips_endpoint must be true or false. use_fips_endpoint:yes (profile:dev)",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_source_profiles_are_errors() {
+        let config = "[profile dev]\nrole_arn = arn:aws:iam::123456789012:role/Dev\nsource_profile = nowhere\n";
+        assert_eq!(
+            lint(config, CREDENTIALS),
+            ["config:3: error: source profile not found: nowhere (profile:dev)"]
+        );
+    }
+}
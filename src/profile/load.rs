@@ -3,6 +3,8 @@ use async_trait::async_trait;
 use crate::profile::ProfileSet;
 
 pub mod aws_sdk;
+pub mod config;
+pub mod dynamic;
 
 #[async_trait]
 pub trait LoadProfiles {
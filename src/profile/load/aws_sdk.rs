@@ -3,12 +3,12 @@ use std::convert::TryFrom;
 
 use async_trait::async_trait;
 use aws_config::profile::load;
-use aws_runtime::env_config::file::EnvConfigFiles;
 use aws_runtime::env_config::section::EnvConfigSections;
 use aws_types::os_shim_internal::{Env, Fs};
 
+use crate::profile::files::ProfileFiles;
 use crate::profile::load::LoadProfiles;
-use crate::profile::{Profile, ProfileSet};
+use crate::profile::{EndpointSettings, Profile, ProfileKind, ProfileSet};
 
 fn profile_from(name: &str, value: &aws_config::profile::Profile) -> anyhow::Result<Profile> {
     fn maybe_s<S: Into<String>>(s: Option<S>) -> Option<String> {
@@ -19,16 +19,41 @@ fn profile_from(name: &str, value: &aws_config::profile::Profile) -> anyhow::Res
         Ok(s.map(|s| s.parse().map(Some)).unwrap_or(Ok(None))?)
     }
 
+    let kind = if value.get("role_arn").is_some() {
+        ProfileKind::Role
+    } else if value.get("sso_start_url").is_some() || value.get("sso_session").is_some() {
+        ProfileKind::Sso
+    } else if value.get("credential_process").is_some() {
+        ProfileKind::CredentialProcess
+    } else if value.get("aws_access_key_id").is_some() {
+        ProfileKind::StaticKeys
+    } else {
+        ProfileKind::Other
+    };
+
     Ok(Profile {
         name: name.to_string(),
+        kind,
         source_profile_name: maybe_s(value.get("source_profile")),
         region_name: maybe_s(value.get("region")),
         role_arn: maybe_s(value.get("role_arn")),
+        credential_source: maybe_s(value.get("credential_source")),
         role_session_name: maybe_s(value.get("role_session_name")),
         external_id: maybe_s(value.get("external_id")),
         duration_seconds: try_n(value.get("duration_seconds"))?,
         scope_down_policy: maybe_s(value.get("scope_down_policy")),
         mfa_serial: maybe_s(value.get("mfa_serial")),
+        endpoint: EndpointSettings {
+            endpoint_url: maybe_s(value.get("endpoint_url")),
+            ca_bundle: maybe_s(value.get("ca_bundle")),
+            sts_regional_endpoints: maybe_s(value.get("sts_regional_endpoints")),
+            use_fips_endpoint: maybe_s(value.get("use_fips_endpoint")),
+            use_dualstack_endpoint: maybe_s(value.get("use_dualstack_endpoint")),
+            ignore_configured_endpoint_urls: maybe_s(value.get("ignore_configured_endpoint_urls")),
+        },
+        tags: BTreeMap::default(),
+        source_file: None,
+        synthesized: false,
     })
 }
 
@@ -46,15 +71,32 @@ impl TryFrom<EnvConfigSections> for ProfileSet {
 
 #[derive(Debug, Default)]
 pub struct AwsSdkProfileLoader {
-    profile_files: EnvConfigFiles,
+    files: ProfileFiles,
     fs: Fs,
     env: Env,
 }
 
+impl From<&ProfileFiles> for AwsSdkProfileLoader {
+    fn from(files: &ProfileFiles) -> Self {
+        AwsSdkProfileLoader {
+            files: files.clone(),
+            ..AwsSdkProfileLoader::default()
+        }
+    }
+}
+
 #[async_trait]
 impl LoadProfiles for AwsSdkProfileLoader {
     async fn load_profiles(&self) -> anyhow::Result<ProfileSet> {
-        let profiles = load(&self.fs, &self.env, &self.profile_files, None).await?;
-        Ok(ProfileSet::try_from(profiles)?)
+        let profile_files = self.files.env_config_files();
+        let profiles = load(&self.fs, &self.env, &profile_files, None).await?;
+        let mut profiles = ProfileSet::try_from(profiles)?;
+
+        for (name, path) in self.files.sources()? {
+            if let Some(profile) = profiles.profiles.get_mut(&name) {
+                profile.source_file = Some(path);
+            }
+        }
+        Ok(profiles)
     }
 }
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::config::{Config, TemplateConfig};
+use crate::partition::Partition;
+use crate::profile::load::LoadProfiles;
+use crate::profile::{Profile, ProfileKind, ProfileSet};
+
+/// Applies the per-profile overrides in the assume-rolers config to the loaded profiles,
+/// after adding the profiles built from the templates.
+pub struct ConfigProfileLoader<L> {
+    loader: L,
+    config: Config,
+}
+
+impl<L> ConfigProfileLoader<L> {
+    pub fn new(loader: L, config: Config) -> Self {
+        ConfigProfileLoader { loader, config }
+    }
+}
+
+/// Builds the profile of the template in the account.
+/// The region falls back to the source profile's, and the endpoints are the source profile's.
+/// The role is in the partition of the region.
+fn from_template(
+    name: &str,
+    template: &TemplateConfig,
+    account: &str,
+    profiles: &ProfileSet,
+) -> Profile {
+    let region_name = template.region.clone().or_else(|| {
+        template.source_profile.as_ref().and_then(|source| {
+            profiles
+                .source_chain(source)
+                .iter()
+                .find_map(|p| p.region_name())
+                .map(|s| s.to_string())
+        })
+    });
+    let partition = region_name
+        .as_deref()
+        .map(Partition::from_region)
+        .unwrap_or_default();
+
+    Profile {
+        name: name.to_string(),
+        kind: ProfileKind::Role,
+        source_profile_name: template.source_profile.clone(),
+        region_name,
+        role_arn: Some(format!(
+            "arn:{}:iam::{}:role/{}",
+            partition,
+            account,
+            template.role_name.trim_start_matches('/')
+        )),
+        credential_source: None,
+        role_session_name: template.role_session_name.clone(),
+        external_id: template.external_id.clone(),
+        duration_seconds: template.duration_seconds,
+        scope_down_policy: None,
+        mfa_serial: template.mfa_serial.clone(),
+        endpoint: template
+            .source_profile
+            .as_ref()
+            .and_then(|source| profiles.get_profile(source))
+            .map(|p| p.endpoint.clone())
+            .unwrap_or_default(),
+        tags: template.tags.clone(),
+        source_file: None,
+        synthesized: true,
+    }
+}
+
+#[async_trait]
+impl<L: LoadProfiles + Send + Sync> LoadProfiles for ConfigProfileLoader<L> {
+    async fn load_profiles(&self) -> anyhow::Result<ProfileSet> {
+        let mut profiles = self.loader.load_profiles().await?;
+
+        let mut templated = BTreeMap::new();
+        for (name, overrides) in self.config.profiles.iter() {
+            let (Some(template_name), Some(account)) =
+                (overrides.template.as_ref(), overrides.account.as_ref())
+            else {
+                continue;
+            };
+            if profiles.get_profile(name).is_some() {
+                debug!(
+                    "the profile in the AWS config takes precedence over the template. profile:{}, template:{}",
+                    name, template_name
+                );
+                continue;
+            }
+            let template =
+                self.config.templates.get(template_name).ok_or_else(|| {
+                    anyhow::anyhow!("No template found. template:{}", template_name)
+                })?;
+            templated.insert(
+                name.to_string(),
+                from_template(name, template, account, &profiles),
+            );
+        }
+        profiles.profiles.extend(templated);
+
+        for (name, overrides) in self.config.profiles.iter() {
+            if let Some(profile) = profiles.profiles.get_mut(name) {
+                if overrides.region.is_some() {
+                    profile.region_name = overrides.region.clone();
+                }
+                if overrides.duration_seconds.is_some() {
+                    profile.duration_seconds = overrides.duration_seconds;
+                }
+                if overrides.role_session_name.is_some() {
+                    profile.role_session_name = overrides.role_session_name.clone();
+                }
+                if overrides.mfa_serial.is_some() {
+                    profile.mfa_serial = overrides.mfa_serial.clone();
+                }
+                profile.tags.extend(overrides.tags.clone());
+            }
+        }
+
+        Ok(profiles)
+    }
+}
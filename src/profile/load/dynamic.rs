@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use crate::partition::Partition;
+use crate::profile::arn::RoleArn;
+use crate::profile::load::LoadProfiles;
+use crate::profile::{Profile, ProfileKind, ProfileSet};
+
+/// A role specified on the command line instead of a profile, e.g. `--account 123456789012 --role OrgAdmin`.
+#[derive(Debug, Clone)]
+pub struct DynamicRole {
+    role_arn: RoleArn,
+    via: Option<String>,
+    /// Whether the partition follows the source profile rather than the ARN given.
+    infer_partition: bool,
+}
+
+impl DynamicRole {
+    /// `via` is the source profile. The default credentials are used without it.
+    pub fn new(role_arn: RoleArn, via: Option<String>) -> Self {
+        DynamicRole {
+            role_arn,
+            via,
+            infer_partition: false,
+        }
+    }
+
+    /// Builds the role in the account, in the partition of the source profile's region or role.
+    pub fn in_account(
+        account_id: &str,
+        role_name: &str,
+        via: Option<String>,
+    ) -> anyhow::Result<Self> {
+        Ok(DynamicRole {
+            role_arn: RoleArn::new(Partition::default(), account_id, role_name)?,
+            via,
+            infer_partition: true,
+        })
+    }
+
+    /// The name of the ad-hoc profile, e.g. `OrgAdmin@123456789012` or `ops/OrgAdmin@123456789012 via acme-base`.
+    /// The path and the source keep the history of different roles apart.
+    pub fn profile_name(&self) -> String {
+        let name = format!(
+            "{}{}@{}",
+            self.role_arn.path.trim_start_matches('/'),
+            self.role_arn.role_name,
+            self.role_arn.account_id
+        );
+        match self.via.as_ref() {
+            Some(via) => format!("{} via {}", name, via),
+            None => name,
+        }
+    }
+
+    /// Builds the profile, inheriting the session name, duration, MFA, region and endpoints from the source profile.
+    fn profile(&self, profiles: &ProfileSet) -> anyhow::Result<Profile> {
+        let via = match self.via.as_ref() {
+            Some(name) => Some(
+                profiles
+                    .get_profile(name)
+                    .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", name))?,
+            ),
+            None => None,
+        };
+        let chain = via
+            .map(|via| profiles.source_chain(via.name()))
+            .unwrap_or_default();
+        let region_name = chain
+            .iter()
+            .find_map(|p| p.region_name())
+            .map(|s| s.to_string());
+        let mut role_arn = self.role_arn.clone();
+        if self.infer_partition {
+            role_arn.partition = match region_name.as_deref() {
+                Some(region) => Partition::from_region(region),
+                None => chain
+                    .iter()
+                    .find_map(|p| p.role())
+                    .map(|arn| arn.partition)
+                    .unwrap_or_default(),
+            };
+        }
+
+        Ok(Profile {
+            name: self.profile_name(),
+            kind: ProfileKind::Role,
+            source_profile_name: self.via.clone(),
+            region_name,
+            role_arn: Some(role_arn.to_string()),
+            credential_source: None,
+            role_session_name: via.and_then(|p| p.role_session_name.clone()),
+            external_id: None,
+            duration_seconds: via.and_then(|p| p.duration_seconds),
+            scope_down_policy: None,
+            mfa_serial: via.and_then(|p| p.mfa_serial.clone()),
+            endpoint: via.map(|p| p.endpoint.clone()).unwrap_or_default(),
+            tags: BTreeMap::default(),
+            source_file: None,
+            synthesized: true,
+        })
+    }
+}
+
+/// Adds the profile of the dynamic role to the loaded profiles.
+pub struct DynamicProfileLoader<L> {
+    loader: L,
+    role: DynamicRole,
+}
+
+impl<L> DynamicProfileLoader<L> {
+    pub fn new(loader: L, role: DynamicRole) -> Self {
+        DynamicProfileLoader { loader, role }
+    }
+}
+
+#[async_trait]
+impl<L: LoadProfiles + Send + Sync> LoadProfiles for DynamicProfileLoader<L> {
+    async fn load_profiles(&self) -> anyhow::Result<ProfileSet> {
+        let mut profiles = self.loader.load_profiles().await?;
+        let profile = self.role.profile(&profiles)?;
+        profiles
+            .profiles
+            .insert(profile.name().to_string(), profile);
+        Ok(profiles)
+    }
+}
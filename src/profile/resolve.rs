@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Resolution {
+    /// The query points to a single profile.
+    Found(String),
+    /// The query matches several profiles.
+    Ambiguous(Vec<String>),
+    /// Nothing matches. Holds similar profile names ranked by edit distance.
+    NotFound(Vec<String>),
+}
+
+/// Resolves a partial profile name by exact, prefix, substring and then fuzzy matching.
+#[derive(Debug, Clone, Default)]
+pub struct NameResolver {
+    /// Pairs of a profile name or an alias, and the profile name it points to.
+    names: Vec<(String, String)>,
+}
+
+impl NameResolver {
+    pub fn new(profile_names: &[String], aliases: &[(String, String)]) -> Self {
+        let names = profile_names
+            .iter()
+            .map(|name| (name.to_string(), name.to_string()))
+            .chain(aliases.iter().cloned())
+            .collect();
+        NameResolver { names }
+    }
+
+    pub fn resolve(&self, query: &str) -> Resolution {
+        if let Some((_, target)) = self.names.iter().find(|(name, _)| name == query) {
+            return Resolution::Found(target.to_string());
+        }
+
+        let matchers: [fn(&str, &str) -> bool; 3] = [
+            |name, query| name.starts_with(query),
+            |name, query| name.contains(query),
+            is_subsequence,
+        ];
+        for matcher in matchers {
+            let targets = self
+                .names
+                .iter()
+                .filter(|(name, _)| matcher(name, query))
+                .map(|(_, target)| target.as_str())
+                .collect::<BTreeSet<_>>();
+            match targets.len() {
+                0 => continue,
+                1 => {
+                    let target = targets.into_iter().next().expect("one target exists");
+                    return Resolution::Found(target.to_string());
+                }
+                _ => {
+                    return Resolution::Ambiguous(
+                        targets.into_iter().map(|s| s.to_string()).collect(),
+                    )
+                }
+            }
+        }
+
+        Resolution::NotFound(self.suggestions(query))
+    }
+
+    fn suggestions(&self, query: &str) -> Vec<String> {
+        let threshold = (query.chars().count() / 3).max(2);
+        let mut ranked = self
+            .names
+            .iter()
+            .map(|(name, _)| (strsim::levenshtein(query, name), name.as_str()))
+            .filter(|(distance, _)| *distance <= threshold)
+            .collect::<Vec<_>>();
+        ranked.sort();
+        ranked
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+}
+
+fn is_subsequence(name: &str, query: &str) -> bool {
+    let mut chars = name.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
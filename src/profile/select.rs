@@ -1,11 +1,74 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::profile::filter::ProfileFilter;
 use crate::profile::{Profile, ProfileSet};
 
+pub mod external;
 pub mod skim;
+pub mod tty;
 
 pub trait SelectProfile {
     fn select_profile<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Option<&'a Profile>>;
 }
 
+pub trait SelectProfiles {
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>>;
+}
+
+/// Orders the candidates: favorites first in the configured order, then the most frequently and recently used.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileOrder {
+    favorites: Vec<String>,
+    frecency: HashMap<String, u32>,
+    filter: ProfileFilter,
+}
+
+impl From<&Config> for ProfileOrder {
+    fn from(config: &Config) -> Self {
+        ProfileOrder {
+            favorites: config
+                .selector
+                .favorites
+                .iter()
+                .map(|name| config.resolve_alias(name).to_string())
+                .collect(),
+            frecency: HashMap::default(),
+            filter: ProfileFilter::default(),
+        }
+    }
+}
+
+impl ProfileOrder {
+    pub fn with_frecency(mut self, frecency: HashMap<String, u32>) -> Self {
+        self.frecency = frecency;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: ProfileFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the profiles which have a role to assume, and match the filter.
+    pub fn candidates<'a>(&self, profiles: &'a ProfileSet) -> Vec<&'a Profile> {
+        let mut targets = profiles
+            .profiles()
+            .filter(|&p| p.role_arn.is_some() && self.filter.matches(p))
+            .collect::<Vec<_>>();
+        targets.sort_by_key(|p| {
+            let favorite = self.favorites.iter().position(|f| f == p.name());
+            let score = self.frecency.get(p.name()).copied().unwrap_or(0);
+            (
+                favorite.unwrap_or(usize::MAX),
+                std::cmp::Reverse(score),
+                p.name(),
+            )
+        });
+        targets
+    }
+}
+
 pub struct StaticProfileSelector {
     profile_name: String,
 }
@@ -28,3 +91,38 @@ impl SelectProfile for StaticProfileSelector {
         }
     }
 }
+
+pub struct StaticProfilesSelector {
+    profile_names: Vec<String>,
+    filter: ProfileFilter,
+}
+
+impl From<Vec<String>> for StaticProfilesSelector {
+    fn from(profile_names: Vec<String>) -> Self {
+        StaticProfilesSelector {
+            profile_names,
+            filter: ProfileFilter::default(),
+        }
+    }
+}
+
+impl StaticProfilesSelector {
+    pub fn with_filter(mut self, filter: ProfileFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+impl SelectProfiles for StaticProfilesSelector {
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>> {
+        self.profile_names
+            .iter()
+            .map(|name| {
+                profiles
+                    .get_profile(name)
+                    .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", name))
+            })
+            .filter(|p| p.as_ref().map(|p| self.filter.matches(p)).unwrap_or(true))
+            .collect()
+    }
+}
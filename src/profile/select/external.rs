@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::FinderConfig;
+use crate::profile::select::{ProfileOrder, SelectProfile, SelectProfiles};
+use crate::profile::{Profile, ProfileSet};
+
+/// Selects profiles with an external fuzzy finder such as fzf, sk, rofi or dmenu.
+pub struct ExternalProfileSelector {
+    finder: FinderConfig,
+    order: ProfileOrder,
+}
+
+impl ExternalProfileSelector {
+    pub fn new(finder: FinderConfig, order: ProfileOrder) -> Self {
+        ExternalProfileSelector { finder, order }
+    }
+
+    fn run<'a>(&self, profiles: &'a ProfileSet, multi: bool) -> anyhow::Result<Vec<&'a Profile>> {
+        let command = if multi {
+            self.finder
+                .multi_command
+                .as_ref()
+                .unwrap_or(&self.finder.command)
+        } else {
+            &self.finder.command
+        };
+        let names = self
+            .order
+            .candidates(profiles)
+            .iter()
+            .map(|p| format!("{}\n", p.name()))
+            .collect::<String>();
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("cannot run the finder. command:{}, {}", command, e))?;
+        let written = child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(names.as_bytes());
+        // the finder may exit without reading all the names.
+        match written {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+        let output = child.wait_with_output()?;
+
+        let stdout = String::from_utf8(output.stdout)?;
+        let selected = stdout
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        // finders exit with non-zero status when the selection is cancelled.
+        if selected.is_empty() {
+            return Ok(Vec::default());
+        }
+        if !output.status.success() {
+            anyhow::bail!(
+                "finder failed. command:{}, status:{}",
+                command,
+                output.status
+            );
+        }
+
+        selected
+            .into_iter()
+            .map(|name| {
+                profiles
+                    .get_profile(name)
+                    .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", name))
+            })
+            .collect()
+    }
+}
+
+impl SelectProfile for ExternalProfileSelector {
+    fn select_profile<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Option<&'a Profile>> {
+        Ok(self.run(profiles, false)?.into_iter().next())
+    }
+}
+
+impl SelectProfiles for ExternalProfileSelector {
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>> {
+        self.run(profiles, true)
+    }
+}
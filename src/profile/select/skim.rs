@@ -1,31 +1,292 @@
-use std::io;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
-use skim::prelude::{SkimItemReader, SkimOptionsBuilder};
-use skim::Skim;
+use skim::prelude::{unbounded, SkimOptionsBuilder};
+use skim::{
+    AnsiString, DisplayContext, ItemPreview, PreviewContext, Skim, SkimItem, SkimItemReceiver,
+    SkimItemSender,
+};
+use tuikit::prelude::{Attr, Color};
 
-use crate::profile::select::SelectProfile;
+use crate::assume_role::cache::SessionCache;
+use crate::config::{Config, EnvironmentConfig, SelectorConfig};
+use crate::profile::select::{ProfileOrder, SelectProfile, SelectProfiles};
 use crate::profile::{Profile, ProfileSet};
 
-pub struct SkimProfileSelector;
+fn color_from(s: &str) -> Option<Color> {
+    match s {
+        "black" => Some(Color::BLACK),
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "yellow" => Some(Color::YELLOW),
+        "blue" => Some(Color::BLUE),
+        "magenta" => Some(Color::MAGENTA),
+        "cyan" => Some(Color::CYAN),
+        "white" => Some(Color::WHITE),
+        _ => s.parse::<u8>().ok().map(Color::AnsiValue),
+    }
+}
 
-impl SelectProfile for SkimProfileSelector {
-    fn select_profile<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Option<&'a Profile>> {
-        let mut names = profiles
-            .profiles()
-            .filter(|&p| p.role_arn.is_some())
-            .map(|p| p.name.as_str())
-            .collect::<Vec<_>>();
-        names.sort();
+struct ProfileItem {
+    name: String,
+    text: String,
+    matching_ranges: Vec<(usize, usize)>,
+    label: Option<(Color, (u32, u32))>,
+    preview: String,
+}
+
+impl SkimItem for ProfileItem {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.text)
+    }
+
+    fn display<'a>(&'a self, context: DisplayContext<'a>) -> AnsiString<'a> {
+        let mut s = AnsiString::from(context);
+        if let Some((color, range)) = self.label {
+            let attr = Attr {
+                fg: color,
+                ..Attr::default()
+            };
+            s.override_attrs(vec![(attr, range)]);
+        }
+        s
+    }
+
+    fn preview(&self, _context: PreviewContext) -> ItemPreview {
+        ItemPreview::Text(self.preview.clone())
+    }
+
+    fn output(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+
+    fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+        Some(&self.matching_ranges)
+    }
+}
+
+pub struct SkimProfileSelector {
+    options: SelectorConfig,
+    aliases: BTreeMap<String, Vec<String>>,
+    environments: Vec<EnvironmentConfig>,
+    order: ProfileOrder,
+    cache: Option<SessionCache>,
+    query: Option<String>,
+}
+
+impl From<&Config> for SkimProfileSelector {
+    fn from(config: &Config) -> Self {
+        let mut aliases = BTreeMap::<String, Vec<String>>::new();
+        for (alias, target) in config.aliases.iter() {
+            aliases
+                .entry(target.to_string())
+                .or_default()
+                .push(alias.to_string());
+        }
+
+        SkimProfileSelector {
+            options: config.selector.clone(),
+            aliases,
+            environments: config.selector.environments.clone(),
+            order: ProfileOrder::from(config),
+            cache: config.cache.enabled.then(SessionCache::default),
+            query: None,
+        }
+    }
+}
+
+impl SkimProfileSelector {
+    pub fn with_order(mut self, order: ProfileOrder) -> Self {
+        self.order = order;
+        self
+    }
 
-        let item_reader = SkimItemReader::default();
-        let items = item_reader.of_bufread(io::Cursor::new(names.join("\n")));
+    /// Starts the selector with the query.
+    pub fn with_query(mut self, query: Option<String>) -> Self {
+        self.query = query;
+        self
+    }
+
+    fn environment_of(&self, profile: &Profile) -> Option<&EnvironmentConfig> {
+        self.environments.iter().find(|env| env.matches(profile))
+    }
+
+    fn item(&self, profile: &Profile, profiles: &ProfileSet, name_width: usize) -> ProfileItem {
+        let name = profile.name();
+        let env = self.environment_of(profile);
+        let env_name = env.map(|e| e.name.as_str()).unwrap_or("");
+        let env_width = self
+            .environments
+            .iter()
+            .map(|e| e.name.len())
+            .max()
+            .unwrap_or(0);
+
+        // columns: name, environment and aliases.
+        let mut text = format!("{:<w$}", name, w = name_width);
+        let mut matching_ranges = vec![(0, name.len())];
+        let env_start = text.chars().count() + 2;
+        text.push_str(&format!("  {:<w$}", env_name, w = env_width));
+        if let Some(aliases) = self.aliases.get(name) {
+            let start = text.len() + 2;
+            text.push_str(&format!("  {}", aliases.join(", ")));
+            matching_ranges.push((start, text.len()));
+        }
+        let text = text.trim_end().to_string();
+
+        let label = env.and_then(|e| {
+            let color = e.color.as_deref().and_then(color_from)?;
+            let range = (
+                env_start as u32,
+                (env_start + env_name.chars().count()) as u32,
+            );
+            Some((color, range))
+        });
+
+        ProfileItem {
+            name: name.to_string(),
+            text,
+            matching_ranges,
+            label,
+            preview: self.preview(profile, profiles, env_name),
+        }
+    }
+
+    fn preview(&self, profile: &Profile, profiles: &ProfileSet, env_name: &str) -> String {
+        fn line(name: &str, value: Option<String>) -> String {
+            format!("{:<16}{}\n", name, value.unwrap_or_else(|| "-".to_string()))
+        }
+
+        let role = profile.role();
+        let chain = profiles.source_chain(profile.name());
+        let region = chain.iter().find_map(|p| p.region_name());
+        let cached = self.cache.as_ref().map(|cache| {
+            cache
+                .get(profile)
+                .and_then(|session| session.remaining())
+                .map(|d| format!("{}m remaining", d.num_minutes()))
+                .unwrap_or_else(|| "none".to_string())
+        });
+
+        [
+            line("profile", Some(profile.name().to_string())),
+            line(
+                "environment",
+                Some(env_name.to_string()).filter(|s| !s.is_empty()),
+            ),
+            line("role arn", profile.role_arn().map(|s| s.to_string())),
+            line(
+                "account id",
+                role.as_ref().map(|r| r.account_id.to_string()),
+            ),
+            line("role name", role.as_ref().map(|r| r.role_name.to_string())),
+            line(
+                "source chain",
+                Some(
+                    chain
+                        .iter()
+                        .map(|p| p.name())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                ),
+            ),
+            line("region", region.map(|s| s.to_string())),
+            line(
+                "mfa",
+                Some(match profile.mfa_serial() {
+                    Some(serial) => format!("required ({})", serial),
+                    None => "not required".to_string(),
+                }),
+            ),
+            line(
+                "duration",
+                profile.duration_seconds().map(|n| format!("{}s", n)),
+            ),
+            line(
+                "file",
+                profile.source_file().map(|p| p.display().to_string()),
+            ),
+            line(
+                "tags",
+                Some(
+                    profile
+                        .tags
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+                .filter(|s| !s.is_empty()),
+            ),
+            line(
+                "cached session",
+                Some(cached.unwrap_or_else(|| "disabled".to_string())),
+            ),
+        ]
+        .concat()
+    }
+}
+
+impl SkimProfileSelector {
+    fn run<'a>(&self, profiles: &'a ProfileSet, multi: bool) -> anyhow::Result<Vec<&'a Profile>> {
+        let targets = self.order.candidates(profiles);
+        let name_width = targets.iter().map(|p| p.name().len()).max().unwrap_or(0);
+
+        let (tx, items): (SkimItemSender, SkimItemReceiver) = unbounded();
+        for profile in targets {
+            tx.send(Arc::new(self.item(profile, profiles, name_width)))?;
+        }
+        drop(tx);
+
+        let layout = self
+            .options
+            .layout
+            .as_deref()
+            .unwrap_or(if self.options.reverse {
+                "reverse"
+            } else {
+                "default"
+            });
+        let mut builder = SkimOptionsBuilder::default();
+        builder
+            .layout(layout)
+            .multi(multi)
+            .query(self.query.as_deref())
+            .prompt(self.options.prompt.as_deref())
+            .bind(self.options.bind.iter().map(|s| s.as_str()).collect());
+        if let Some(height) = self.options.height.as_deref() {
+            builder.height(Some(height));
+        }
+        if self.options.preview {
+            // items provide their own preview, but skim needs a preview command to show the pane.
+            builder.preview(Some(""));
+        }
+        if let Some(preview_window) = self.options.preview_window.as_deref() {
+            builder.preview_window(Some(preview_window));
+        }
+        let options = builder.build()?;
 
-        let options = SkimOptionsBuilder::default().reverse(true).build()?;
         let selected = Skim::run_with(&options, Some(items))
             .and_then(|out| (!out.is_abort).then_some(out.selected_items))
             .unwrap_or_default();
 
-        let selected_name = selected.into_iter().next().map(|x| x.output().to_string());
-        Ok(selected_name.and_then(|name| profiles.get_profile(&name)))
+        Ok(selected
+            .into_iter()
+            .filter_map(|x| profiles.get_profile(&x.output()))
+            .collect())
+    }
+}
+
+impl SelectProfile for SkimProfileSelector {
+    fn select_profile<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Option<&'a Profile>> {
+        Ok(self.run(profiles, false)?.into_iter().next())
+    }
+}
+
+impl SelectProfiles for SkimProfileSelector {
+    /// Marks the profiles with tab, and accepts them with enter.
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>> {
+        self.run(profiles, true)
     }
 }
@@ -0,0 +1,86 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+use crate::profile::select::{ProfileOrder, SelectProfile, SelectProfiles};
+use crate::profile::{Profile, ProfileSet};
+
+const TTY_PATH: &str = "/dev/tty";
+
+/// Selects profiles by number on the controlling terminal, for when stdin isn't a terminal.
+pub struct TtyProfileSelector {
+    order: ProfileOrder,
+}
+
+impl From<ProfileOrder> for TtyProfileSelector {
+    fn from(order: ProfileOrder) -> Self {
+        TtyProfileSelector { order }
+    }
+}
+
+impl TtyProfileSelector {
+    fn open_tty() -> anyhow::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(TTY_PATH)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "cannot select a profile in non-interactive mode. specify a profile, or configure selector.finder. {}",
+                    e
+                )
+            })
+    }
+
+    fn run<'a>(&self, profiles: &'a ProfileSet, multi: bool) -> anyhow::Result<Vec<&'a Profile>> {
+        let candidates = self.order.candidates(profiles);
+        if candidates.is_empty() {
+            return Ok(Vec::default());
+        }
+
+        let mut tty = Self::open_tty()?;
+        let width = candidates.len().to_string().len();
+        for (i, profile) in candidates.iter().enumerate() {
+            writeln!(tty, "{:>w$}) {}", i + 1, profile.name(), w = width)?;
+        }
+        if multi {
+            write!(tty, "profile numbers (separated by spaces): ")?;
+        } else {
+            write!(tty, "profile number: ")?;
+        }
+        tty.flush()?;
+
+        let mut line = String::new();
+        BufReader::new(tty).read_line(&mut line)?;
+
+        let numbers = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        if !multi && numbers.len() > 1 {
+            anyhow::bail!("only one profile can be selected. input:{}", line.trim());
+        }
+
+        numbers
+            .into_iter()
+            .map(|s| {
+                s.parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| candidates.get(i).copied())
+                    .ok_or_else(|| anyhow::anyhow!("invalid profile number. input:{}", s))
+            })
+            .collect()
+    }
+}
+
+impl SelectProfile for TtyProfileSelector {
+    fn select_profile<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Option<&'a Profile>> {
+        Ok(self.run(profiles, false)?.into_iter().next())
+    }
+}
+
+impl SelectProfiles for TtyProfileSelector {
+    fn select_profiles<'a>(&self, profiles: &'a ProfileSet) -> anyhow::Result<Vec<&'a Profile>> {
+        self.run(profiles, true)
+    }
+}
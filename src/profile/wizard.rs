@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+
+use skim::prelude::{unbounded, SkimOptionsBuilder};
+use skim::{Skim, SkimItemReceiver, SkimItemSender};
+
+use crate::profile::arn::RoleArn;
+use crate::profile::edit::Assignment;
+use crate::profile::lint::is_mfa_serial;
+use crate::profile::{EndpointSettings, Profile, ProfileKind, ProfileSet};
+
+/// A role profile filled in by the wizard.
+#[derive(Debug, Clone)]
+pub struct NewProfile {
+    pub name: String,
+    pub role_arn: RoleArn,
+    pub source_profile: String,
+    pub mfa_serial: Option<String>,
+    pub region: Option<String>,
+}
+
+impl NewProfile {
+    /// Builds the profile to test the assumption before it's written.
+    pub fn profile(&self) -> Profile {
+        Profile {
+            name: self.name.to_string(),
+            kind: ProfileKind::Role,
+            source_profile_name: Some(self.source_profile.to_string()),
+            region_name: self.region.clone(),
+            role_arn: Some(self.role_arn.to_string()),
+            credential_source: None,
+            role_session_name: None,
+            external_id: None,
+            duration_seconds: None,
+            scope_down_policy: None,
+            mfa_serial: self.mfa_serial.clone(),
+            endpoint: EndpointSettings::default(),
+            tags: BTreeMap::default(),
+            source_file: None,
+            synthesized: true,
+        }
+    }
+
+    /// The properties of the section, in the order they are written.
+    pub fn properties(&self) -> Vec<Assignment> {
+        [
+            ("role_arn", Some(self.role_arn.to_string())),
+            ("source_profile", Some(self.source_profile.to_string())),
+            ("mfa_serial", self.mfa_serial.clone()),
+            ("region", self.region.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            value.map(|value| Assignment {
+                key: key.to_string(),
+                value,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Asks for the line, showing the default in brackets. An empty answer takes the default.
+fn ask(label: &str, default: Option<&str>) -> anyhow::Result<String> {
+    match default {
+        Some(default) => eprint!("{} [{}]: ", label, default),
+        None => eprint!("{}: ", label),
+    }
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        anyhow::bail!("canceled.");
+    }
+    let answer = answer.trim();
+    Ok(match default {
+        Some(default) if answer.is_empty() => default.to_string(),
+        _ => answer.to_string(),
+    })
+}
+
+/// Whether the answer clears an optional value, since an empty answer takes the default.
+fn is_none(answer: &str) -> bool {
+    matches!(answer, "" | "-" | "none")
+}
+
+/// Asks again until the answer is valid.
+fn ask_until<T, F: Fn(&str) -> anyhow::Result<T>>(
+    label: &str,
+    default: Option<&str>,
+    parse: F,
+) -> anyhow::Result<T> {
+    loop {
+        let answer = ask(label, default)?;
+        match parse(&answer) {
+            Ok(value) => return Ok(value),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// Prompts for the fields of a role profile.
+pub struct ProfileWizard<'a> {
+    profiles: &'a ProfileSet,
+}
+
+impl<'a> ProfileWizard<'a> {
+    pub fn new(profiles: &'a ProfileSet) -> Self {
+        ProfileWizard { profiles }
+    }
+
+    pub fn run(&self, name: Option<String>) -> anyhow::Result<NewProfile> {
+        if !io::stdin().is_terminal() {
+            anyhow::bail!("the wizard requires a terminal. use `profile add` instead.");
+        }
+
+        let role_arn = ask_until("role ARN", None, |s| s.parse::<RoleArn>())?;
+        let source_profile = self.source_profile()?;
+        eprintln!("source profile: {}", source_profile);
+
+        let chain = self.profiles.source_chain(&source_profile);
+        let source_mfa_serial = chain.iter().find_map(|p| p.mfa_serial());
+        let mfa_serial = ask_until("MFA serial (- for none)", source_mfa_serial, |s| match s {
+            s if is_none(s) => Ok(None),
+            s if is_mfa_serial(s) => Ok(Some(s.to_string())),
+            s => Err(anyhow::anyhow!("invalid MFA serial. mfa_serial:{}", s)),
+        })?;
+        let source_region = chain.iter().find_map(|p| p.region_name());
+        let region = ask("region (- for none)", source_region)?;
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                let default = role_arn.role_name.to_lowercase();
+                ask_until("profile name", Some(&default), |s| {
+                    if s.is_empty() || s.contains(char::is_whitespace) || s.contains(['[', ']']) {
+                        Err(anyhow::anyhow!("invalid profile name. profile_name:{}", s))
+                    } else if self.profiles.get_profile(s).is_some() {
+                        Err(anyhow::anyhow!(
+                            "The profile already exists. profile_name:{}",
+                            s
+                        ))
+                    } else {
+                        Ok(s.to_string())
+                    }
+                })?
+            }
+        };
+
+        Ok(NewProfile {
+            name,
+            role_arn,
+            source_profile,
+            mfa_serial,
+            region: Some(region).filter(|s| !is_none(s)),
+        })
+    }
+
+    /// Offers the existing profiles with skim.
+    fn source_profile(&self) -> anyhow::Result<String> {
+        let mut names = self.profiles.names().collect::<Vec<_>>();
+        names.sort();
+        if names.is_empty() {
+            anyhow::bail!("No profile found. add a profile with credentials first.");
+        }
+
+        let (tx, items): (SkimItemSender, SkimItemReceiver) = unbounded();
+        for name in names {
+            tx.send(Arc::new(name.to_string()))?;
+        }
+        drop(tx);
+        let options = SkimOptionsBuilder::default()
+            .prompt(Some("source profile> "))
+            .height(Some("40%"))
+            .layout("reverse")
+            .build()?;
+
+        Skim::run_with(&options, Some(items))
+            .filter(|out| !out.is_abort)
+            .and_then(|out| out.selected_items.into_iter().next())
+            .map(|item| item.output().to_string())
+            .ok_or_else(|| anyhow::anyhow!("canceled."))
+    }
+}
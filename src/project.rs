@@ -0,0 +1,75 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::project::trust::TrustStore;
+
+pub mod cli;
+pub mod trust;
+
+pub const PROJECT_FILE_NAME: &str = ".assume-rolers.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub plugin: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub digest: String,
+    pub config: ProjectConfig,
+}
+
+impl ProjectFile {
+    /// Searches the project file upward from the current directory.
+    pub fn discover() -> anyhow::Result<Option<ProjectFile>> {
+        let cwd = env::current_dir()?;
+        Self::find_from(&cwd)
+    }
+
+    /// Searches the project file, and ignores it with a notice unless it's trusted.
+    pub fn discover_trusted() -> anyhow::Result<Option<ProjectFile>> {
+        if let Some(project) = Self::discover()? {
+            if TrustStore::load()?.is_trusted(&project) {
+                return Ok(Some(project));
+            }
+
+            eprintln!(
+                "ignored untrusted project file: {}. run `assume-rolers trust` to use it.",
+                project.path.display()
+            );
+        }
+
+        Ok(None)
+    }
+
+    pub fn find_from(dir: &Path) -> anyhow::Result<Option<ProjectFile>> {
+        dir.ancestors()
+            .map(|d| d.join(PROJECT_FILE_NAME))
+            .find(|p| p.is_file())
+            .map(Self::from_file)
+            .transpose()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<ProjectFile> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        let config = toml::from_str::<ProjectConfig>(&text)
+            .with_context(|| format!("invalid project file. path:{}", path.display()))?;
+
+        Ok(ProjectFile {
+            path: path.canonicalize()?,
+            digest: hex::encode(Sha256::digest(text.as_bytes())),
+            config,
+        })
+    }
+}
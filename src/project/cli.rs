@@ -0,0 +1,127 @@
+use clap::ArgAction;
+
+use crate::assume_role::aws_sdk::AwsSdkAssumeRole;
+use crate::assume_role::cache::CachedAssumeRole;
+use crate::command::direnv::DirenvCommand;
+use crate::config::Config;
+use crate::history::History;
+use crate::mfa::MfaReader;
+use crate::network::NetworkSettings;
+use crate::profile::files::ProfileFiles;
+use crate::profile::load::aws_sdk::AwsSdkProfileLoader;
+use crate::profile::load::config::ConfigProfileLoader;
+use crate::profile::select::StaticProfileSelector;
+use crate::project::trust::TrustStore;
+use crate::project::{ProjectFile, PROJECT_FILE_NAME};
+use crate::run::AssumeRolers;
+use crate::settings::{Layer, Settings};
+
+#[derive(Debug)]
+pub struct Direnv {
+    token: Option<String>,
+}
+
+impl Direnv {
+    pub fn command() -> clap::Command {
+        clap::Command::new("direnv")
+            .about(format!(
+                "Assume the profile in {} and show the variables for direnv.",
+                PROJECT_FILE_NAME
+            ))
+            .arg(
+                clap::Arg::new("token")
+                    .short('t')
+                    .long("token")
+                    .value_hint(clap::ValueHint::Other)
+                    .help("Specify a token code provided by the MFA device."),
+            )
+    }
+
+    pub async fn run(self, config: Config, files: ProfileFiles) -> anyhow::Result<()> {
+        let project = ProjectFile::discover()?
+            .ok_or_else(|| anyhow::anyhow!("{} not found.", PROJECT_FILE_NAME))?;
+        if !TrustStore::load()?.is_trusted(&project) {
+            anyhow::bail!(
+                "{} is not trusted. review it and run `assume-rolers trust`.",
+                project.path.display()
+            );
+        }
+
+        let settings = Settings::resolve(Layer::default(), Some(&project.config), &config);
+        let profile = settings.profile.ok_or_else(|| {
+            anyhow::anyhow!("no profile specified. path:{}", project.path.display())
+        })?;
+
+        // direnv reloads .envrc when the watched file changes.
+        println!("watch_file '{}'", project.path.display());
+
+        let assume_rolers = AssumeRolers::new(
+            ConfigProfileLoader::new(AwsSdkProfileLoader::from(&files), config.clone()),
+            StaticProfileSelector::from(profile.value),
+            MfaReader::new(self.token.as_ref(), &config.mfa),
+            CachedAssumeRole::new(
+                AwsSdkAssumeRole::from(&files).with_network(NetworkSettings::from(&config.network)),
+                config.cache.clone(),
+            ),
+            DirenvCommand::new(config.exports()),
+        )
+        .with_region(settings.region.map(|s| s.value))
+        .with_default_region(settings.default_region.map(|s| s.value))
+        .with_history(History::load());
+        assume_rolers.run().await?;
+        Ok(())
+    }
+}
+
+impl From<&clap::ArgMatches> for Direnv {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Direnv {
+            token: m.get_one::<String>("token").map(|s| s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Trust {
+    revoke: bool,
+}
+
+impl Trust {
+    pub fn command() -> clap::Command {
+        clap::Command::new("trust")
+            .about(format!(
+                "Trust {} in the current directory or its parents.",
+                PROJECT_FILE_NAME
+            ))
+            .arg(
+                clap::Arg::new("revoke")
+                    .long("revoke")
+                    .action(ArgAction::SetTrue)
+                    .help("Revoke the trust instead."),
+            )
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let project = ProjectFile::discover()?
+            .ok_or_else(|| anyhow::anyhow!("{} not found.", PROJECT_FILE_NAME))?;
+
+        let mut store = TrustStore::load()?;
+        if self.revoke {
+            if store.revoke(&project) {
+                eprintln!("revoked: {}", project.path.display());
+            }
+        } else {
+            store.trust(&project);
+            eprintln!("trusted: {}", project.path.display());
+        }
+        store.save()
+    }
+}
+
+impl From<&clap::ArgMatches> for Trust {
+    fn from(m: &clap::ArgMatches) -> Self {
+        Trust {
+            revoke: m.get_flag("revoke"),
+        }
+    }
+}
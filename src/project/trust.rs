@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+use crate::project::ProjectFile;
+
+/// Remembers the project files the user has reviewed, keyed by path and pinned to their content.
+/// A trusted file becomes untrusted again once its content changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(skip)]
+    path: PathBuf,
+    trusted: BTreeMap<PathBuf, String>,
+}
+
+impl TrustStore {
+    pub fn load() -> anyhow::Result<TrustStore> {
+        let path = dirs::data_dir()
+            .map(|dir| dir.join("trusted.json"))
+            .ok_or_else(|| anyhow::anyhow!("cannot determine the data directory."))?;
+        Self::from_file(path)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<TrustStore> {
+        let path = path.as_ref();
+        let mut store = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str::<TrustStore>(&json)
+                .with_context(|| format!("invalid trust store. path:{}", path.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => TrustStore::default(),
+            Err(e) => return Err(e).with_context(|| format!("cannot read {}", path.display())),
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    pub fn is_trusted(&self, project: &ProjectFile) -> bool {
+        self.trusted.get(&project.path) == Some(&project.digest)
+    }
+
+    pub fn trust(&mut self, project: &ProjectFile) {
+        self.trusted
+            .insert(project.path.clone(), project.digest.clone());
+    }
+
+    pub fn revoke(&mut self, project: &ProjectFile) -> bool {
+        self.trusted.remove(&project.path).is_some()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("cannot write {}", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+/// The standard variables of the region, in the order of precedence, as AWS SDKs read them.
+pub const ENV_VARS: [&str; 2] = ["AWS_REGION", "AWS_DEFAULT_REGION"];
+
+/// Returns whether the region looks like `us-east-1`, `us-gov-west-1` or `cn-north-1`.
+pub fn is_region(s: &str) -> bool {
+    let Some((words, number)) = s.rsplit_once('-') else {
+        return false;
+    };
+    words.contains('-')
+        && words
+            .split('-')
+            .all(|w| !w.is_empty() && w.bytes().all(|b| b.is_ascii_lowercase()))
+        && !number.is_empty()
+        && number.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Fails with the setting to fix if the region is invalid.
+pub fn validate(region: &str) -> anyhow::Result<()> {
+    if is_region(region) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "invalid region. expected a region code such as us-east-1. region:{}",
+            region
+        ))
+    }
+}
@@ -1,10 +1,14 @@
 use crate::assume_role::AssumeRole;
 use crate::command::Command;
+use crate::history::{History, HistoryEntry};
 use crate::mfa::ReadMfaToken;
 use crate::profile::load::LoadProfiles;
-use crate::profile::select::SelectProfile;
+use crate::profile::select::{SelectProfile, SelectProfiles};
+use crate::profile::{Profile, ProfileSet};
+use crate::region;
 use assume_rolers_schema::credentials::ProfileCredentials;
-use tracing::debug;
+use chrono::Utc;
+use tracing::{debug, warn};
 
 pub struct AssumeRolers<L, S, R, A, C> {
     loader: L,
@@ -12,16 +16,12 @@ pub struct AssumeRolers<L, S, R, A, C> {
     mfa_reader: R,
     assume_role: A,
     command: C,
+    region_name: Option<String>,
+    default_region_name: Option<String>,
+    history: Option<History>,
 }
 
-impl<L, S, R, A, C> AssumeRolers<L, S, R, A, C>
-where
-    L: LoadProfiles + Send + Sync + 'static,
-    S: SelectProfile,
-    R: ReadMfaToken + Send + Sync + 'static,
-    A: AssumeRole + Send + Sync + 'static,
-    C: Command,
-{
+impl<L, S, R, A, C> AssumeRolers<L, S, R, A, C> {
     pub fn new(loader: L, selector: S, mfa_reader: R, assume_role: A, command: C) -> Self {
         Self {
             loader,
@@ -29,29 +29,141 @@ where
             mfa_reader,
             assume_role,
             command,
+            region_name: None,
+            default_region_name: None,
+            history: None,
         }
     }
 
-    pub async fn run(self) -> anyhow::Result<()> {
+    /// Overrides the region of the selected profile.
+    pub fn with_region(mut self, region_name: Option<String>) -> Self {
+        self.region_name = region_name;
+        self
+    }
+
+    /// The region when neither the override nor the profiles specify one.
+    pub fn with_default_region(mut self, region_name: Option<String>) -> Self {
+        self.default_region_name = region_name;
+        self
+    }
+
+    /// Records successful assumptions to the history.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+}
+
+impl<L, S, R, A, C> AssumeRolers<L, S, R, A, C>
+where
+    L: LoadProfiles + Send + Sync + 'static,
+    R: ReadMfaToken + Send + Sync + 'static,
+    A: AssumeRole + Send + Sync + 'static,
+    C: Command,
+{
+    /// The override takes precedence over the profile and its sources, and the default follows them.
+    /// STS is called in the region of the profiles, since the override may not be enabled for the source.
+    async fn assume(
+        &mut self,
+        profile: &Profile,
+        profiles: &ProfileSet,
+    ) -> anyhow::Result<ProfileCredentials> {
+        debug!("target profile:{}", profile.name);
+        let profile_region_name = profiles
+            .source_chain(profile.name())
+            .iter()
+            .find_map(|p| p.region_name())
+            .map(|s| s.to_string());
+        let region_name = self
+            .region_name
+            .clone()
+            .or_else(|| profile_region_name.clone())
+            .or_else(|| self.default_region_name.clone());
+        if let Some(region_name) = region_name.as_deref() {
+            region::validate(region_name)
+                .map_err(|e| e.context(format!("profile:{}", profile.name())))?;
+        }
+
+        let mut profile = profile.clone();
+        profile.region_name = profile_region_name;
+        let mut result = self
+            .assume_role
+            .assume_role(&profile, self.mfa_reader.clone())
+            .await?;
+        result.region_name = region_name.unwrap_or_default();
+
+        if let Some(history) = self.history.as_mut() {
+            let entry = HistoryEntry {
+                profile_name: profile.name().to_string(),
+                role_arn: profile.role_arn.clone(),
+                source_profile: profile.source_profile_name.clone(),
+                region_name: result.region_name.clone(),
+                assumed_at: Utc::now(),
+                expires_at: result.credentials.expires_at(),
+            };
+            if let Err(e) = history.record(entry) {
+                warn!("cannot record the history. error:{:?}", e);
+            }
+        }
+
+        Ok(ProfileCredentials {
+            profile_name: profile.name().to_string(),
+            region_name: result.region_name,
+            credentials: result.credentials,
+            role_arn: profile.role_arn().map(|s| s.to_string()),
+            ca_bundle: profile.endpoint.ca_bundle.clone(),
+        })
+    }
+
+    pub async fn run(mut self) -> anyhow::Result<()>
+    where
+        S: SelectProfile,
+    {
         let profiles = self.loader.load_profiles().await?;
         if let Some(profile) = self.selector.select_profile(&profiles)? {
-            debug!("target profile:{}", profile.name);
-            let result = self
-                .assume_role
-                .assume_role(profile, self.mfa_reader)
-                .await?;
-
-            self.command
-                .run(ProfileCredentials {
-                    profile_name: profile.name().to_string(),
-                    region_name: result.region_name,
-                    credentials: result.credentials,
-                })
-                .await?;
+            let credentials = self.assume(profile, &profiles).await?;
+            self.command.run(credentials).await?;
         } else {
             debug!("no profile selected.")
         }
 
         Ok(())
     }
+
+    /// Runs a copy of the command for each selected profile, and continues even if some of them fail.
+    pub async fn run_batch(mut self) -> anyhow::Result<()>
+    where
+        S: SelectProfiles,
+        C: Clone,
+    {
+        let profiles = self.loader.load_profiles().await?;
+        let selected = self.selector.select_profiles(&profiles)?;
+        if selected.is_empty() {
+            debug!("no profile selected.");
+            return Ok(());
+        }
+
+        let mut failures = Vec::new();
+        for profile in selected {
+            eprintln!("==> {} <==", profile.name());
+            let result = match self.assume(profile, &profiles).await {
+                Ok(credentials) => self.command.clone().run(credentials).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("error: {:?}", e);
+                failures.push(profile.name());
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "failed for {} profile(s): {}",
+                failures.len(),
+                failures.join(", ")
+            ))
+        }
+    }
 }
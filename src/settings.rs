@@ -0,0 +1,144 @@
+use std::env;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::config::{env_vars, Config};
+use crate::project::ProjectConfig;
+use crate::region;
+
+/// Where a setting came from, in the order of precedence.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Cli,
+    Env,
+    ProjectFile,
+    UserConfig,
+    AwsConfig,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Source::Cli => "cli",
+            Source::Env => "env",
+            Source::ProjectFile => "project-file",
+            Source::UserConfig => "user-config",
+            Source::AwsConfig => "aws-config",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T: fmt::Display> fmt::Display for Setting<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.value, self.source)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Layer {
+    pub profile: Option<String>,
+    pub plugin: Option<String>,
+    pub region: Option<String>,
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|s| !s.is_empty())
+}
+
+impl Layer {
+    /// `AWS_REGION` is not an override, since it is what assume-rolers exports for the last profile.
+    pub fn from_env() -> Layer {
+        Layer {
+            profile: None,
+            plugin: var(env_vars::PLUGIN),
+            region: var(env_vars::REGION),
+        }
+    }
+}
+
+impl From<&ProjectConfig> for Layer {
+    fn from(project: &ProjectConfig) -> Self {
+        Layer {
+            profile: project.profile.clone(),
+            plugin: project.plugin.clone(),
+            region: project.region.clone(),
+        }
+    }
+}
+
+impl From<&Config> for Layer {
+    fn from(config: &Config) -> Self {
+        Layer {
+            profile: None,
+            plugin: config.plugin.clone(),
+            region: None,
+        }
+    }
+}
+
+/// Settings resolved from the layers, before a profile is selected.
+/// The AWS config is applied per profile by the profile loader.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub profile: Option<Setting<String>>,
+    pub plugin: Option<Setting<String>>,
+    pub region: Option<Setting<String>>,
+    /// The region when neither the override nor the profiles specify one,
+    /// i.e. `AWS_REGION`, `AWS_DEFAULT_REGION`, then `default_region` in the user config.
+    pub default_region: Option<Setting<String>>,
+}
+
+impl Settings {
+    pub fn resolve(cli: Layer, project: Option<&ProjectConfig>, config: &Config) -> Settings {
+        let layers = [
+            Some((Source::Cli, cli)),
+            Some((Source::Env, Layer::from_env())),
+            project.map(|p| (Source::ProjectFile, Layer::from(p))),
+            Some((Source::UserConfig, Layer::from(config))),
+        ];
+
+        let pick = |f: fn(&Layer) -> Option<&String>| {
+            layers.iter().flatten().find_map(|(source, layer)| {
+                f(layer).map(|value| Setting {
+                    value: value.to_string(),
+                    source: *source,
+                })
+            })
+        };
+
+        let profile = pick(|l| l.profile.as_ref()).map(|s| Setting {
+            value: config.resolve_alias(&s.value).to_string(),
+            source: s.source,
+        });
+
+        let default_region = region::ENV_VARS
+            .iter()
+            .find_map(|name| var(name))
+            .map(|value| Setting {
+                value,
+                source: Source::Env,
+            })
+            .or_else(|| {
+                config.default_region.clone().map(|value| Setting {
+                    value,
+                    source: Source::UserConfig,
+                })
+            });
+
+        Settings {
+            profile,
+            plugin: pick(|l| l.plugin.as_ref()),
+            region: pick(|l| l.region.as_ref()),
+            default_region,
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs;
+
+use aws_config::BehaviorVersion;
+use aws_types::region::Region;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::dirs;
+use crate::network::NetworkSettings;
+use crate::profile::files::FileKind;
+use crate::profile::ini::{IniFile, Section};
+
+/// Where the IAM Identity Center access portal is, from an `sso-session` section or a legacy SSO profile.
+#[derive(Debug, Clone)]
+pub struct SsoSettings {
+    pub session_name: Option<String>,
+    pub start_url: String,
+    pub region: String,
+}
+
+/// Returns the section with the highest precedence.
+fn find_section<'a>(
+    files: &'a [(FileKind, IniFile)],
+    name: &str,
+) -> Option<(&'a IniFile, &'a Section)> {
+    files
+        .iter()
+        .filter(|(kind, _)| *kind == FileKind::Config)
+        .flat_map(|(_, file)| file.sections.iter().map(move |s| (file, s)))
+        .rfind(|(_, s)| s.name == name)
+}
+
+fn required(file: &IniFile, section: &Section, key: &str) -> anyhow::Result<String> {
+    section
+        .get(key)
+        .map(|p| p.value.to_string())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} is required. path:{}, section:{}",
+                key,
+                file.path.display(),
+                section.name
+            )
+        })
+}
+
+impl SsoSettings {
+    pub fn from_session(files: &[(FileKind, IniFile)], session_name: &str) -> anyhow::Result<Self> {
+        let (file, section) = find_section(files, &format!("sso-session {}", session_name))
+            .ok_or_else(|| anyhow::anyhow!("No sso-session found. sso_session:{}", session_name))?;
+        Ok(SsoSettings {
+            session_name: Some(session_name.to_string()),
+            start_url: required(file, section, "sso_start_url")?,
+            region: required(file, section, "sso_region")?,
+        })
+    }
+
+    /// Uses the `sso_session` of the profile, or its legacy `sso_start_url` and `sso_region`.
+    pub fn from_profile(files: &[(FileKind, IniFile)], profile_name: &str) -> anyhow::Result<Self> {
+        let section_name = if profile_name == "default" {
+            profile_name.to_string()
+        } else {
+            format!("profile {}", profile_name)
+        };
+        let (file, section) = find_section(files, &section_name)
+            .ok_or_else(|| anyhow::anyhow!("No profile found. profile_name:{}", profile_name))?;
+        if let Some(session) = section.get("sso_session") {
+            return Self::from_session(files, &session.value);
+        }
+        Ok(SsoSettings {
+            session_name: None,
+            start_url: required(file, section, "sso_start_url")?,
+            region: required(file, section, "sso_region")?,
+        })
+    }
+
+    /// The properties which the generated profiles sign in with.
+    pub fn profile_properties(&self) -> Vec<(String, String)> {
+        match self.session_name.as_ref() {
+            Some(name) => vec![("sso_session".to_string(), name.to_string())],
+            None => vec![
+                ("sso_start_url".to_string(), self.start_url.to_string()),
+                ("sso_region".to_string(), self.region.to_string()),
+            ],
+        }
+    }
+
+    /// Returns the access token cached by `aws sso login`, which hasn't expired yet.
+    pub fn cached_token(&self) -> anyhow::Result<String> {
+        let not_found = || {
+            let login = match self.session_name.as_ref() {
+                Some(name) => format!("aws sso login --sso-session {}", name),
+                None => "aws sso login".to_string(),
+            };
+            anyhow::anyhow!(
+                "No valid SSO token found. run `{}` first. sso_start_url:{}",
+                login,
+                self.start_url
+            )
+        };
+        let dir = dirs::aws_sso_cache_dir().ok_or_else(not_found)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!(
+                    "cannot read the SSO cache. path:{}, error:{}",
+                    dir.display(),
+                    e
+                );
+                return Err(not_found());
+            }
+        };
+
+        let now = Utc::now();
+        let start_url = self.start_url.trim_end_matches('/');
+        entries
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| {
+                let json = fs::read_to_string(e.path()).ok()?;
+                // client registrations are in the same directory, without tokens.
+                serde_json::from_str::<CachedToken>(&json).ok()
+            })
+            .filter(|t| t.start_url.trim_end_matches('/') == start_url)
+            .filter_map(|t| t.expires_at().filter(|at| *at > now).map(|at| (at, t)))
+            .max_by_key(|(at, _)| *at)
+            .map(|(_, t)| t.access_token)
+            .ok_or_else(not_found)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedToken {
+    start_url: String,
+    access_token: String,
+    expires_at: String,
+}
+
+impl CachedToken {
+    fn expires_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.expires_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            // older versions of the CLI write `2019-11-14T04:05:45UTC`.
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(&self.expires_at, "%Y-%m-%dT%H:%M:%SUTC")
+                    .map(|dt| DateTime::<Utc>::from_utc(dt, Utc))
+                    .ok()
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SsoAccount {
+    pub id: String,
+    pub name: String,
+}
+
+/// Lists the accounts and roles which the signed-in user can access.
+pub struct SsoClient {
+    client: aws_sdk_sso::Client,
+    access_token: String,
+    network: NetworkSettings,
+}
+
+impl SsoClient {
+    /// The endpoint can be overridden by `AWS_ENDPOINT_URL_SSO`, as AWS SDKs do.
+    pub async fn new(
+        settings: &SsoSettings,
+        access_token: String,
+        network: &NetworkSettings,
+    ) -> anyhow::Result<Self> {
+        let config = aws_config::defaults(BehaviorVersion::v2024_03_28())
+            .region(Region::new(settings.region.to_string()))
+            .http_client(network.sdk_client()?)
+            .timeout_config(network.timeout_config())
+            .no_credentials()
+            .load()
+            .await;
+        Ok(SsoClient {
+            client: aws_sdk_sso::Client::new(&config),
+            access_token,
+            network: network.clone(),
+        })
+    }
+
+    pub async fn list_accounts(&self) -> anyhow::Result<Vec<SsoAccount>> {
+        let accounts = self
+            .client
+            .list_accounts()
+            .access_token(&self.access_token)
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| self.network.sdk_error("SSO", e))?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|a| {
+                let id = a.account_id?;
+                // an empty name would render an empty or duplicate profile name.
+                let name = a
+                    .account_name
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| id.clone());
+                Some(SsoAccount { id, name })
+            })
+            .collect())
+    }
+
+    pub async fn list_account_roles(&self, account_id: &str) -> anyhow::Result<Vec<String>> {
+        let roles = self
+            .client
+            .list_account_roles()
+            .access_token(&self.access_token)
+            .account_id(account_id)
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| self.network.sdk_error("SSO", e))?;
+        Ok(roles.into_iter().filter_map(|r| r.role_name).collect())
+    }
+}